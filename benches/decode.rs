@@ -0,0 +1,56 @@
+//! Compares decoding typed messages straight from the token stream with decoding
+//! them through a `Value` tree. Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use tiny_json_rs::mapper::Value;
+use tiny_json_rs::serializer::{self, Deserialize};
+use tiny_json_rs::Deserialize;
+
+#[derive(Deserialize)]
+pub struct Reading {
+    pub sensor: String,
+    pub value: f64,
+    pub flags: Vec<bool>,
+    pub count: u32,
+}
+
+#[derive(Deserialize)]
+pub struct Batch {
+    pub id: u64,
+    pub readings: Vec<Reading>,
+}
+
+fn document() -> String {
+    let reading = r#"{"sensor": "probe-7", "value": 21.5, "flags": [true, false], "count": 3}"#;
+    let readings = vec![reading; 1000].join(",");
+    format!(r#"{{"id": 42, "readings": [{}]}}"#, readings)
+}
+
+fn time<F: FnMut()>(name: &str, mut run: F) -> Duration {
+    const RUNS: u32 = 200;
+    run();
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        run();
+    }
+    let per_run = start.elapsed() / RUNS;
+    println!("{:<12} {:>10.1?} per document", name, per_run);
+    per_run
+}
+
+fn main() {
+    let json = document();
+    let streamed = time("streamed", || {
+        let batch: Batch = tiny_json_rs::decode(black_box(json.clone())).unwrap();
+        black_box(batch);
+    });
+    let tree = time("value tree", || {
+        let value: Value = black_box(json.as_str()).parse().unwrap();
+        black_box(Batch::deserialize(Some(&value)).unwrap());
+    });
+    println!(
+        "streaming takes {:.2}x the time of the tree",
+        streamed.as_secs_f64() / tree.as_secs_f64()
+    );
+}
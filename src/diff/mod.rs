@@ -0,0 +1,220 @@
+use crate::jsonpath::child_path;
+use crate::mapper::Value;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ArrayMode {
+    /// Elements are compared position by position.
+    #[default]
+    Ordered,
+    /// Elements are matched regardless of position: objects by the value of the
+    /// named member, anything else by the element itself.
+    KeyedBy(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DiffOptions {
+    pub arrays: ArrayMode,
+}
+
+/// A single difference between two documents. `old` is `None` for added values and
+/// `new` is `None` for removed ones.
+///
+/// `path` is a JSONPath expression that selects the value in the document it
+/// belongs to, such as `$.devices[1].temp` or `$.devices[?@.id=="a1"].temp` when
+/// arrays are keyed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "~ {}: {} -> {}", self.path, old, new),
+            (None, Some(new)) => write!(f, "+ {}: {}", self.path, new),
+            (Some(old), None) => write!(f, "- {}: {}", self.path, old),
+            (None, None) => write!(f, "  {}", self.path),
+        }
+    }
+}
+
+struct Differ<'a> {
+    options: &'a DiffOptions,
+    changes: Vec<Change>,
+}
+
+impl Differ<'_> {
+    fn push(&mut self, path: String, old: Option<&Value>, new: Option<&Value>) {
+        self.changes.push(Change {
+            path,
+            old: old.cloned(),
+            new: new.cloned(),
+        });
+    }
+
+    fn compare(&mut self, old: &Value, new: &Value, path: String) {
+        match (old, new) {
+            (Value::Object(old), Value::Object(new)) => {
+                for (key, old_value) in old {
+                    let child = child_path(&path, key);
+                    match new.get(key) {
+                        Some(new_value) => self.compare(old_value, new_value, child),
+                        None => self.push(child, Some(old_value), None),
+                    }
+                }
+                for (key, new_value) in new {
+                    if !old.contains_key(key) {
+                        self.push(child_path(&path, key), None, Some(new_value));
+                    }
+                }
+            }
+            (Value::Array(old), Value::Array(new)) => match &self.options.arrays {
+                ArrayMode::Ordered => self.compare_ordered(old, new, path),
+                ArrayMode::KeyedBy(field) => self.compare_keyed(old, new, path, field),
+            },
+            _ => {
+                if !old.equivalent(new) {
+                    self.push(path, Some(old), Some(new));
+                }
+            }
+        }
+    }
+
+    fn compare_ordered(&mut self, old: &[Value], new: &[Value], path: String) {
+        for i in 0..old.len().max(new.len()) {
+            let child = format!("{}[{}]", path, i);
+            match (old.get(i), new.get(i)) {
+                (Some(old_value), Some(new_value)) => self.compare(old_value, new_value, child),
+                (old_value, new_value) => self.push(child, old_value, new_value),
+            }
+        }
+    }
+
+    fn compare_keyed(&mut self, old: &[Value], new: &[Value], path: String, field: &str) {
+        let mut matched = vec![false; new.len()];
+        for (i, old_value) in old.iter().enumerate() {
+            let key = element_key(old_value, field);
+            let found = new.iter().enumerate().position(|(j, new_value)| {
+                !matched[j] && element_key(new_value, field).equivalent(key)
+            });
+            match found {
+                Some(j) => {
+                    matched[j] = true;
+                    let child = keyed_path(&path, old_value, field, i);
+                    self.compare(old_value, &new[j], child);
+                }
+                None => self.push(
+                    keyed_path(&path, old_value, field, i),
+                    Some(old_value),
+                    None,
+                ),
+            }
+        }
+        for (j, new_value) in new.iter().enumerate() {
+            if !matched[j] {
+                self.push(
+                    keyed_path(&path, new_value, field, j),
+                    None,
+                    Some(new_value),
+                );
+            }
+        }
+    }
+}
+
+fn element_key<'a>(element: &'a Value, field: &str) -> &'a Value {
+    match element {
+        Value::Object(object) => object.get(field).unwrap_or(element),
+        _ => element,
+    }
+}
+
+fn keyed_path(path: &str, element: &Value, field: &str, index: usize) -> String {
+    let key = element_key(element, field);
+    match (key, core::ptr::eq(key, element)) {
+        (Value::Token(_), false) => format!("{}[?{}=={}]", path, child_path("@", field), key),
+        (Value::Token(_), true) => format!("{}[?@=={}]", path, key),
+        _ => format!("{}[{}]", path, index),
+    }
+}
+
+/// Lists the differences between `old` and `new`, comparing arrays by position.
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    diff_with(old, new, &DiffOptions::default())
+}
+
+pub fn diff_with(old: &Value, new: &Value, options: &DiffOptions) -> Vec<Change> {
+    let mut differ = Differ {
+        options,
+        changes: Vec::new(),
+    };
+    differ.compare(old, new, String::from("$"));
+    differ.changes
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{ArrayMode, DiffOptions};
+    use crate::mapper::Value;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    const OLD: &str = r#"{"name": "hub", "devices": [{"id": "a1", "temp": 38}, {"id": "b2", "temp": 41}], "tags": ["x", "y"], "my key": 1}"#;
+    const NEW: &str = r#"{"name": "hub", "devices": [{"id": "b2", "temp": 42}, {"id": "c3", "temp": 20}], "tags": ["y"], "my key": 1.0, "on": true}"#;
+
+    fn render(options: &DiffOptions) -> Vec<String> {
+        let old: Value = OLD.parse().unwrap();
+        let new: Value = NEW.parse().unwrap();
+        super::diff_with(&old, &new, options)
+            .iter()
+            .map(|change| change.to_string())
+            .collect()
+    }
+
+    #[test]
+    pub fn test_diff_ordered() {
+        assert_eq!(
+            render(&DiffOptions::default()),
+            [
+                r#"~ $.devices[0].id: "a1" -> "b2""#,
+                "~ $.devices[0].temp: 38 -> 42",
+                r#"~ $.devices[1].id: "b2" -> "c3""#,
+                "~ $.devices[1].temp: 41 -> 20",
+                r#"~ $.tags[0]: "x" -> "y""#,
+                r#"- $.tags[1]: "y""#,
+                "+ $.on: true",
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_diff_keyed() {
+        let options = DiffOptions {
+            arrays: ArrayMode::KeyedBy("id".to_string()),
+        };
+        assert_eq!(
+            render(&options),
+            [
+                r#"- $.devices[?@.id=="a1"]: {"id":"a1","temp":38}"#,
+                r#"~ $.devices[?@.id=="b2"].temp: 41 -> 42"#,
+                r#"+ $.devices[?@.id=="c3"]: {"id":"c3","temp":20}"#,
+                r#"- $.tags[?@=="x"]: "x""#,
+                "+ $.on: true",
+            ]
+        );
+
+        let old: Value = OLD.parse().unwrap();
+        let change = &super::diff_with(&old, &NEW.parse().unwrap(), &options)[0];
+        assert_eq!(
+            old.query(&change.path).unwrap(),
+            [change.old.as_ref().unwrap()]
+        );
+    }
+}
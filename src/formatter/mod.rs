@@ -0,0 +1,783 @@
+use crate::lexer::{Token, TokenType};
+use crate::mapper::Value;
+use crate::serializer::{Scalar, Serializer};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Result, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Newline {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl Newline {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// What to write for floats that JSON has no syntax for: NaN and the infinities.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NonFinite {
+    /// Fail the encoding with a `core::fmt::Error`.
+    Error,
+    /// Write `null`, as JavaScript's `JSON.stringify` does.
+    #[default]
+    Null,
+    /// Write the strings `"NaN"`, `"Infinity"` and `"-Infinity"`.
+    String,
+}
+
+/// Options shared by every JSON encoder.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EncodeOptions {
+    pub non_finite: NonFinite,
+    /// Writes integers outside the 64-bit range, which only `i128` and `u128` can
+    /// hold, as strings, since JavaScript and many other consumers cannot read
+    /// numbers that wide. Applies to streamed values and [`Value`] trees alike.
+    pub int128_as_string: bool,
+}
+
+/// Layout options for pretty-printed JSON. The default matches `{:#}` on a
+/// [`Value`]: two-space indentation, `": "` between keys and values and one
+/// element per line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrettyConfig {
+    pub indent: String,
+    pub space_after_colon: bool,
+    /// Only affects arrays kept on a single line, other commas end the line.
+    pub space_after_comma: bool,
+    pub newline: Newline,
+    /// Keeps non-empty arrays of scalars on one line when the whole line, indentation
+    /// included, fits within this many columns.
+    pub inline_array_width: Option<usize>,
+    pub options: EncodeOptions,
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        PrettyConfig {
+            indent: String::from("  "),
+            space_after_colon: true,
+            space_after_comma: true,
+            newline: Newline::Lf,
+            inline_array_width: None,
+            options: EncodeOptions::default(),
+        }
+    }
+}
+
+pub(crate) fn write_escaped<W: Write>(out: &mut W, input: &str) -> Result {
+    out.write_char('"')?;
+    for ch in input.chars() {
+        match ch {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            '\u{8}' => out.write_str("\\b")?,
+            '\u{c}' => out.write_str("\\f")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// Fixed-capacity text buffer for formatting numbers without allocating.
+struct StackBuffer {
+    bytes: [u8; 40],
+    len: usize,
+}
+
+impl StackBuffer {
+    fn new() -> Self {
+        StackBuffer {
+            bytes: [0; 40],
+            len: 0,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Write for StackBuffer {
+    fn write_str(&mut self, s: &str) -> Result {
+        let end = self.len + s.len();
+        if end > self.bytes.len() {
+            return Err(core::fmt::Error);
+        }
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+fn write_zeros<W: Write>(out: &mut W, count: usize) -> Result {
+    for _ in 0..count {
+        out.write_char('0')?;
+    }
+    Ok(())
+}
+
+/// Rewrites the `{:e}` rendering of a float, which already holds its shortest
+/// round-trip digits, as a JSON number. Like JavaScript, plain decimal notation
+/// is used for exponents from -6 to 20 and scientific notation otherwise.
+fn write_shortest<W: Write>(out: &mut W, scientific: &str) -> Result {
+    let (mantissa, exponent) = scientific.split_once('e').ok_or(core::fmt::Error)?;
+    let exponent: i32 = exponent.parse().map_err(|_| core::fmt::Error)?;
+    let mantissa = match mantissa.strip_prefix('-') {
+        Some(mantissa) => {
+            out.write_char('-')?;
+            mantissa
+        }
+        None => mantissa,
+    };
+    let (lead, rest) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    if !(-6..21).contains(&exponent) {
+        out.write_str(lead)?;
+        if !rest.is_empty() {
+            out.write_char('.')?;
+            out.write_str(rest)?;
+        }
+        return write!(out, "e{}", exponent);
+    }
+
+    if exponent < 0 {
+        out.write_str("0.")?;
+        write_zeros(out, (-exponent - 1) as usize)?;
+        out.write_str(lead)?;
+        return out.write_str(rest);
+    }
+
+    let point = exponent as usize;
+    out.write_str(lead)?;
+    if rest.len() <= point {
+        out.write_str(rest)?;
+        write_zeros(out, point - rest.len())?;
+        out.write_str(".0")
+    } else {
+        out.write_str(&rest[..point])?;
+        out.write_char('.')?;
+        out.write_str(&rest[point..])
+    }
+}
+
+pub(crate) fn non_finite_name(value: f64) -> &'static str {
+    if value.is_nan() {
+        "NaN"
+    } else if value > 0.0 {
+        "Infinity"
+    } else {
+        "-Infinity"
+    }
+}
+
+fn non_finite_literal(literal: &str) -> Option<f64> {
+    match literal {
+        "NaN" => Some(f64::NAN),
+        "Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        _ => None,
+    }
+}
+
+fn write_non_finite<W: Write>(out: &mut W, value: f64, options: &EncodeOptions) -> Result {
+    match options.non_finite {
+        NonFinite::Error => Err(core::fmt::Error),
+        NonFinite::Null => out.write_str("null"),
+        NonFinite::String => write_escaped(out, non_finite_name(value)),
+    }
+}
+
+const DIGIT_PAIRS: &[u8; 200] = b"\
+0001020304050607080910111213141516171819\
+2021222324252627282930313233343536373839\
+4041424344454647484950515253545556575859\
+6061626364656667686970717273747576777879\
+8081828384858687888990919293949596979899";
+
+/// Formats `value` into the tail of `bytes` two digits at a time, returning the
+/// index of the first digit.
+fn format_u64(mut value: u64, bytes: &mut [u8]) -> usize {
+    let mut start = bytes.len();
+    while value >= 100 {
+        let pair = (value % 100) as usize * 2;
+        value /= 100;
+        start -= 2;
+        bytes[start..start + 2].copy_from_slice(&DIGIT_PAIRS[pair..pair + 2]);
+    }
+    if value >= 10 {
+        let pair = value as usize * 2;
+        start -= 2;
+        bytes[start..start + 2].copy_from_slice(&DIGIT_PAIRS[pair..pair + 2]);
+    } else {
+        start -= 1;
+        bytes[start] = b'0' + value as u8;
+    }
+    start
+}
+
+/// Writes `value` in decimal without going through `core::fmt` or allocating.
+pub fn write_u64<W: Write>(out: &mut W, value: u64) -> Result {
+    let mut bytes = [0; 20];
+    let start = format_u64(value, &mut bytes);
+    // Only ASCII digits were written
+    out.write_str(core::str::from_utf8(&bytes[start..]).map_err(|_| core::fmt::Error)?)
+}
+
+pub fn write_i64<W: Write>(out: &mut W, value: i64) -> Result {
+    if value < 0 {
+        out.write_char('-')?;
+    }
+    write_u64(out, value.unsigned_abs())
+}
+
+/// Writes `value` in decimal, formatting it in 19 digit chunks that each fit a `u64`.
+pub fn write_u128<W: Write>(out: &mut W, mut value: u128) -> Result {
+    const CHUNK: u128 = 10_000_000_000_000_000_000;
+    let mut bytes = [0; 40];
+    let mut end = bytes.len();
+    while value > u64::MAX as u128 {
+        let chunk = &mut bytes[end - 19..end];
+        let start = format_u64((value % CHUNK) as u64, chunk);
+        chunk[..start].fill(b'0');
+        value /= CHUNK;
+        end -= 19;
+    }
+    let start = format_u64(value as u64, &mut bytes[..end]);
+    out.write_str(core::str::from_utf8(&bytes[start..]).map_err(|_| core::fmt::Error)?)
+}
+
+pub fn write_i128<W: Write>(out: &mut W, value: i128) -> Result {
+    if value < 0 {
+        out.write_char('-')?;
+    }
+    write_u128(out, value.unsigned_abs())
+}
+
+/// Writes `value` in its shortest form that parses back to the same `f64`.
+pub fn write_f64<W: Write>(out: &mut W, value: f64, options: &EncodeOptions) -> Result {
+    if !value.is_finite() {
+        return write_non_finite(out, value, options);
+    }
+    let mut buffer = StackBuffer::new();
+    write!(buffer, "{:e}", value)?;
+    write_shortest(out, buffer.as_str())
+}
+
+/// Writes `value` in its shortest form that parses back to the same `f32`.
+pub fn write_f32<W: Write>(out: &mut W, value: f32, options: &EncodeOptions) -> Result {
+    if !value.is_finite() {
+        return write_non_finite(out, value as f64, options);
+    }
+    let mut buffer = StackBuffer::new();
+    write!(buffer, "{:e}", value)?;
+    write_shortest(out, buffer.as_str())
+}
+
+/// Whether `literal` is an integer too wide for `i64` and `u64`.
+fn is_wide_integer(literal: &str) -> bool {
+    !literal.contains(['.', 'e', 'E'])
+        && literal.parse::<i64>().is_err()
+        && literal.parse::<u64>().is_err()
+}
+
+/// Writes a preformatted numeric literal, applying the non-finite policy to the
+/// names [`non_finite_name`] gives to NaN and the infinities, and quoting wide
+/// integers when `int128_as_string` is set.
+fn write_number<W: Write>(out: &mut W, literal: &str, options: &EncodeOptions) -> Result {
+    match non_finite_literal(literal) {
+        Some(value) => write_non_finite(out, value, options),
+        None if options.int128_as_string && is_wide_integer(literal) => {
+            out.write_char('"')?;
+            out.write_str(literal)?;
+            out.write_char('"')
+        }
+        None => out.write_str(literal),
+    }
+}
+
+fn write_token<W: Write>(out: &mut W, token: &Token, options: &EncodeOptions) -> Result {
+    match token.token_type {
+        TokenType::String(_) => write_escaped(out, &token.literal),
+        TokenType::Int | TokenType::Float => write_number(out, &token.literal, options),
+        _ => out.write_str(&token.literal),
+    }
+}
+
+/// Adapts a `std::io::Write` sink so the JSON writers can stream into it. The first
+/// I/O error is kept in `error` since `core::fmt::Error` cannot carry it.
+#[cfg(feature = "std")]
+pub struct IoWriter<W> {
+    pub inner: W,
+    pub error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> IoWriter<W> {
+    pub fn new(inner: W) -> Self {
+        IoWriter { inner, error: None }
+    }
+
+    /// Turns the outcome of a formatting call into the underlying I/O result.
+    pub fn finish(self, result: Result) -> std::io::Result<()> {
+        match (result, self.error) {
+            (_, Some(error)) => Err(error),
+            (Err(_), None) => Err(std::io::Error::other("formatter error")),
+            (Ok(()), None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            core::fmt::Error
+        })
+    }
+}
+
+/// Writes `value` as minified JSON.
+pub fn write_compact<W: Write>(value: &Value, out: &mut W, options: &EncodeOptions) -> Result {
+    match value {
+        Value::Token(token) => write_token(out, token, options),
+        Value::Object(object) => {
+            out.write_char('{')?;
+            for (i, (key, value)) in object.iter().enumerate() {
+                if i > 0 {
+                    out.write_char(',')?;
+                }
+                write_escaped(out, key)?;
+                out.write_char(':')?;
+                write_compact(value, out, options)?;
+            }
+            out.write_char('}')
+        }
+        Value::Array(array) => {
+            out.write_char('[')?;
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    out.write_char(',')?;
+                }
+                write_compact(value, out, options)?;
+            }
+            out.write_char(']')
+        }
+    }
+}
+
+// How many open containers fit in `JsonSerializer::objects`
+const WORD_BITS: usize = u128::BITS as usize;
+
+/// A [`Serializer`] writing compact JSON straight into `out` as events arrive.
+/// It only allocates for values nested more than 128 levels deep.
+pub struct JsonSerializer<'a, W> {
+    out: &'a mut W,
+    options: EncodeOptions,
+    // Set once a value is complete, so whatever follows it needs a separating comma
+    after_value: bool,
+    // One bit per open container, set for objects
+    objects: u128,
+    // The bits of the outer containers, once more are open than `objects` holds
+    outer: Vec<u128>,
+    depth: usize,
+}
+
+impl<'a, W: Write> JsonSerializer<'a, W> {
+    pub fn new(out: &'a mut W) -> Self {
+        JsonSerializer::with_options(out, &EncodeOptions::default())
+    }
+
+    pub fn with_options(out: &'a mut W, options: &EncodeOptions) -> Self {
+        JsonSerializer {
+            out,
+            options: *options,
+            after_value: false,
+            objects: 0,
+            outer: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    fn begin(&mut self, object: bool) -> Result {
+        if self.depth > 0 && self.depth.is_multiple_of(WORD_BITS) {
+            self.outer.push(self.objects);
+            self.objects = 0;
+        }
+        if self.after_value {
+            self.out.write_char(',')?;
+        }
+        self.after_value = false;
+        self.objects = (self.objects << 1) | object as u128;
+        self.depth += 1;
+        self.out.write_char(if object { '{' } else { '[' })
+    }
+}
+
+impl<W: Write> Serializer for JsonSerializer<'_, W> {
+    type Error = core::fmt::Error;
+
+    fn begin_object(&mut self) -> Result {
+        self.begin(true)
+    }
+
+    fn begin_array(&mut self) -> Result {
+        self.begin(false)
+    }
+
+    fn key(&mut self, key: &str) -> Result {
+        if self.after_value {
+            self.out.write_char(',')?;
+        }
+        self.after_value = false;
+        write_escaped(self.out, key)?;
+        self.out.write_char(':')
+    }
+
+    fn value(&mut self, value: Scalar<'_>) -> Result {
+        if self.after_value {
+            self.out.write_char(',')?;
+        }
+        self.after_value = true;
+        match value {
+            Scalar::Null => self.out.write_str("null"),
+            Scalar::Bool(value) => write!(self.out, "{}", value),
+            Scalar::I64(value) => write_i64(self.out, value),
+            Scalar::U64(value) => write_u64(self.out, value),
+            Scalar::I128(value)
+                if self.options.int128_as_string && i64::try_from(value).is_err() =>
+            {
+                self.out.write_char('"')?;
+                write_i128(self.out, value)?;
+                self.out.write_char('"')
+            }
+            Scalar::U128(value)
+                if self.options.int128_as_string && u64::try_from(value).is_err() =>
+            {
+                self.out.write_char('"')?;
+                write_u128(self.out, value)?;
+                self.out.write_char('"')
+            }
+            Scalar::I128(value) => write_i128(self.out, value),
+            Scalar::U128(value) => write_u128(self.out, value),
+            Scalar::F32(value) => write_f32(self.out, value, &self.options),
+            Scalar::F64(value) => write_f64(self.out, value, &self.options),
+            Scalar::Number(literal) => write_number(self.out, literal, &self.options),
+            Scalar::Str(value) => write_escaped(self.out, value),
+        }
+    }
+
+    // `core::fmt::Error` has no room for the message
+    fn error(_message: &'static str) -> core::fmt::Error {
+        core::fmt::Error
+    }
+
+    fn end(&mut self) -> Result {
+        if self.depth == 0 {
+            return Err(core::fmt::Error);
+        }
+        let object = self.objects & 1 == 1;
+        self.objects >>= 1;
+        self.depth -= 1;
+        if self.depth > 0 && self.depth.is_multiple_of(WORD_BITS) {
+            self.objects = self.outer.pop().unwrap_or(0);
+        }
+        self.after_value = true;
+        self.out.write_char(if object { '}' } else { ']' })
+    }
+}
+
+/// Writes `value` as indented JSON laid out according to `config`.
+pub fn write_pretty<W: Write>(value: &Value, out: &mut W, config: &PrettyConfig) -> Result {
+    let mut writer = PrettyWriter {
+        out,
+        config,
+        column: 0,
+    };
+    writer.write_value(value, 0)
+}
+
+struct PrettyWriter<'a, W> {
+    out: &'a mut W,
+    config: &'a PrettyConfig,
+    column: usize,
+}
+
+impl<W: Write> Write for PrettyWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> Result {
+        match s.rfind('\n') {
+            Some(newline) => self.column = s[newline + 1..].chars().count(),
+            None => self.column += s.chars().count(),
+        }
+        self.out.write_str(s)
+    }
+}
+
+impl<W: Write> PrettyWriter<'_, W> {
+    fn write_indent(&mut self, depth: usize) -> Result {
+        self.write_str(self.config.newline.as_str())?;
+        for _ in 0..depth {
+            self.write_str(&self.config.indent)?;
+        }
+        Ok(())
+    }
+
+    fn write_inline(&mut self, array: &[Value]) -> Result {
+        self.write_char('[')?;
+        for (i, value) in array.iter().enumerate() {
+            if i > 0 {
+                self.write_char(',')?;
+                if self.config.space_after_comma {
+                    self.write_char(' ')?;
+                }
+            }
+            write_compact(value, self, &self.config.options)?;
+        }
+        self.write_char(']')
+    }
+
+    fn fits_inline(&self, array: &[Value]) -> bool {
+        let width = match self.config.inline_array_width {
+            Some(width) => width,
+            None => return false,
+        };
+        if !array.iter().all(|value| matches!(value, Value::Token(_))) {
+            return false;
+        }
+
+        let mut measure = PrettyWriter {
+            out: &mut String::new(),
+            config: self.config,
+            column: self.column,
+        };
+        measure.write_inline(array).is_ok() && measure.column <= width
+    }
+
+    fn write_value(&mut self, value: &Value, depth: usize) -> Result {
+        match value {
+            Value::Token(token) => write_token(self, token, &self.config.options),
+            Value::Object(object) if object.is_empty() => self.write_str("{}"),
+            Value::Array(array) if array.is_empty() => self.write_str("[]"),
+            Value::Object(object) => {
+                self.write_char('{')?;
+                for (i, (key, value)) in object.iter().enumerate() {
+                    if i > 0 {
+                        self.write_char(',')?;
+                    }
+                    self.write_indent(depth + 1)?;
+                    write_escaped(self, key)?;
+                    self.write_char(':')?;
+                    if self.config.space_after_colon {
+                        self.write_char(' ')?;
+                    }
+                    self.write_value(value, depth + 1)?;
+                }
+                self.write_indent(depth)?;
+                self.write_char('}')
+            }
+            Value::Array(array) if self.fits_inline(array) => self.write_inline(array),
+            Value::Array(array) => {
+                self.write_char('[')?;
+                for (i, value) in array.iter().enumerate() {
+                    if i > 0 {
+                        self.write_char(',')?;
+                    }
+                    self.write_indent(depth + 1)?;
+                    self.write_value(value, depth + 1)?;
+                }
+                self.write_indent(depth)?;
+                self.write_char(']')
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{EncodeOptions, Newline, NonFinite, PrettyConfig};
+    use crate::mapper::Value;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    const JSON: &str =
+        r#"{"name": "sensor", "samples": [1, 2, 3], "nested": [[10, 20], {"k": []}]}"#;
+
+    #[test]
+    pub fn test_inline_arrays() {
+        let value: Value = JSON.parse().unwrap();
+        let config = PrettyConfig {
+            indent: "    ".to_string(),
+            inline_array_width: Some(24),
+            ..PrettyConfig::default()
+        };
+
+        assert_eq!(
+            value.encode_pretty(&config).unwrap(),
+            r#"{
+    "name": "sensor",
+    "nested": [
+        [10, 20],
+        {
+            "k": []
+        }
+    ],
+    "samples": [1, 2, 3]
+}"#
+        );
+
+        let narrow = PrettyConfig {
+            inline_array_width: Some(23),
+            ..config
+        };
+        assert!(value
+            .encode_pretty(&narrow)
+            .unwrap()
+            .contains("\"samples\": [\n"));
+    }
+
+    #[test]
+    pub fn test_separators() {
+        let value: Value = r#"{"a": [true, null], "b": 1}"#.parse().unwrap();
+        let config = PrettyConfig {
+            indent: "\t".to_string(),
+            space_after_colon: false,
+            space_after_comma: false,
+            newline: Newline::CrLf,
+            inline_array_width: Some(80),
+            options: Default::default(),
+        };
+
+        let output = value.encode_pretty(&config).unwrap();
+        assert_eq!(output, "{\r\n\t\"a\":[true,null],\r\n\t\"b\":1\r\n}");
+        assert_eq!(output.parse::<Value>().unwrap(), value);
+        assert_eq!(
+            value.encode_pretty(&PrettyConfig::default()).unwrap(),
+            String::from("{\n  \"a\": [\n    true,\n    null\n  ],\n  \"b\": 1\n}")
+        );
+    }
+
+    #[test]
+    pub fn test_float_formatting() {
+        assert_eq!(crate::encode(1e300f64), "1e300");
+        assert_eq!(crate::encode(0.1f32), "0.1");
+        assert_eq!(crate::encode(1.0f64), "1.0");
+        assert_eq!(crate::encode(-0.0f64), "-0.0");
+        assert_eq!(crate::encode(1e-7f64), "1e-7");
+        assert_eq!(crate::encode(-1.5e-7f64), "-1.5e-7");
+        assert_eq!(crate::encode(0.000001f64), "0.000001");
+        assert_eq!(crate::encode(123456.789f64), "123456.789");
+        assert_eq!(crate::encode(1e20f64), "100000000000000000000.0");
+        assert_eq!(crate::encode(1e21f64), "1e21");
+        assert_eq!(crate::encode(f64::MAX), "1.7976931348623157e308");
+        assert_eq!(crate::encode(vec![0.3f32, 2.5f32]), "[0.3,2.5]");
+
+        for value in [0.1, 1.0 / 3.0, 5e-324, 2.0f64.powi(60), -9.87654321e-12] {
+            let encoded = crate::encode(value);
+            assert_eq!(crate::decode::<f64>(encoded).unwrap(), value);
+            assert_eq!(
+                crate::serializer::Serialize::serialize(&value).to_string(),
+                crate::encode(value)
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_non_finite() {
+        let values = vec![1.5, f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+        let with = |non_finite| {
+            let options = EncodeOptions {
+                non_finite,
+                ..Default::default()
+            };
+            crate::encode_with(values.clone(), &options)
+        };
+
+        assert_eq!(with(NonFinite::Null).unwrap(), "[1.5,null,null,null]");
+        assert_eq!(crate::encode(values.clone()), "[1.5,null,null,null]");
+        assert!(with(NonFinite::Error).is_err());
+
+        let strings = with(NonFinite::String).unwrap();
+        assert_eq!(strings, r#"[1.5,"NaN","Infinity","-Infinity"]"#);
+        // Reading them back takes opting in
+        assert!(crate::decode::<Vec<f64>>(strings.clone()).is_err());
+        let options = crate::serializer::DecodeOptions {
+            coercion: crate::serializer::Coercion {
+                non_finite_from_strings: true,
+                ..crate::serializer::Coercion::STRICT
+            },
+        };
+        let decoded: Vec<f64> = crate::decode_with(strings, &options).unwrap();
+        assert!(decoded[1].is_nan());
+        assert_eq!(decoded[2..], [f64::INFINITY, f64::NEG_INFINITY]);
+        let nan: crate::mapper::Value = r#""NaN""#.parse().unwrap();
+        assert!(<f32 as crate::serializer::Deserialize>::deserialize(Some(&nan)).is_err());
+        let infinity = r#""Infinity""#.to_string();
+        assert!(crate::decode::<f32>(infinity.clone()).is_err());
+        assert_eq!(
+            crate::decode_with::<f32>(infinity, &options),
+            Ok(f32::INFINITY)
+        );
+
+        // The Value path applies the same policy when the tree is written out
+        let tree = crate::serializer::Serialize::serialize(&values);
+        assert_eq!(tree.to_string(), "[1.5,null,null,null]");
+        let config = PrettyConfig {
+            options: EncodeOptions {
+                non_finite: NonFinite::String,
+                ..Default::default()
+            },
+            inline_array_width: Some(80),
+            ..Default::default()
+        };
+        assert_eq!(
+            tree.encode_pretty(&config).unwrap(),
+            r#"[1.5, "NaN", "Infinity", "-Infinity"]"#
+        );
+        let config = PrettyConfig {
+            options: EncodeOptions {
+                non_finite: NonFinite::Error,
+                ..Default::default()
+            },
+            ..config
+        };
+        assert!(tree.encode_pretty(&config).is_err());
+        assert!(crate::encode_pretty(vec![1.5, f64::NAN, 2.0], &config).is_err());
+    }
+
+    #[test]
+    pub fn test_integer_formatting() {
+        let mut output = String::new();
+        for value in [0, 7, 10, 99, 100, 1234567, u64::MAX] {
+            output.clear();
+            super::write_u64(&mut output, value).unwrap();
+            assert_eq!(output, value.to_string());
+        }
+        for value in [0, -1, -10, 42, -9876543210, i64::MIN, i64::MAX] {
+            output.clear();
+            super::write_i64(&mut output, value).unwrap();
+            assert_eq!(output, value.to_string());
+        }
+
+        assert_eq!(
+            crate::encode(vec![u8::MAX as i64, -128, i64::MIN]),
+            "[255,-128,-9223372036854775808]"
+        );
+        assert_eq!(
+            crate::serializer::Serialize::serialize(&(-300i16)).to_string(),
+            "-300"
+        );
+    }
+}
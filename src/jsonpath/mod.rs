@@ -0,0 +1,817 @@
+use crate::lexer::{self, LexerError, StringType, Token, TokenType};
+use crate::mapper::Value;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::str::FromStr;
+
+#[derive(Debug, PartialEq)]
+pub enum PathError {
+    UnexpectedEnd,
+    UnexpectedChar(char, usize),
+    InvalidLiteral(String),
+    /// A comparison operand that may select more than one node, at the given position.
+    NonSingularQuery(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Comparable {
+    Literal(Value),
+    Query(Query),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Exists(Query),
+    Compare(Comparable, CompareOp, Comparable),
+}
+
+/// A compiled RFC 9535 JSONPath query such as `$.devices[?(@.temp > 40)].id`.
+///
+/// Supported are name, index, wildcard, slice and filter selectors, unions and
+/// recursive descent. Filter expressions may compare singular queries and literals
+/// and combine them with `&&`, `||` and `!`; function extensions are not supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    query: Query,
+}
+
+impl JsonPath {
+    pub fn parse(input: &str) -> Result<JsonPath, PathError> {
+        let mut parser = Parser::new(input);
+        parser.skip_whitespace();
+        parser.expect('$')?;
+        let segments = parser.parse_segments()?;
+        parser.skip_whitespace();
+        if let Some(ch) = parser.peek() {
+            return Err(PathError::UnexpectedChar(ch, parser.position));
+        }
+
+        Ok(JsonPath {
+            query: Query {
+                relative: false,
+                segments,
+            },
+        })
+    }
+
+    /// Returns every node of `root` selected by this query, in document order.
+    pub fn query<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        self.query.evaluate(root, root)
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = PathError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        JsonPath::parse(input)
+    }
+}
+
+impl Value {
+    pub fn query(&self, path: &str) -> Result<Vec<&Value>, PathError> {
+        Ok(JsonPath::parse(path)?.query(self))
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Parser {
+        Parser {
+            chars: input.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn read_char(&mut self) -> Result<char, PathError> {
+        let ch = self.peek().ok_or(PathError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(ch)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), PathError> {
+        let ch = self.read_char()?;
+        if ch != expected {
+            return Err(PathError::UnexpectedChar(ch, self.position - 1));
+        }
+        Ok(())
+    }
+
+    fn unexpected(&self) -> PathError {
+        match self.peek() {
+            Some(ch) => PathError::UnexpectedChar(ch, self.position),
+            None => PathError::UnexpectedEnd,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn consume(&mut self, text: &str) -> bool {
+        let matched = text
+            .chars()
+            .enumerate()
+            .all(|(i, ch)| self.peek_at(i) == Some(ch));
+        if matched {
+            self.position += text.chars().count();
+        }
+        matched
+    }
+
+    fn parse_segments(&mut self) -> Result<Vec<Segment>, PathError> {
+        let mut segments = Vec::new();
+        loop {
+            let start = self.position;
+            self.skip_whitespace();
+            match self.peek() {
+                Some('[') => segments.push(Segment {
+                    descendant: false,
+                    selectors: self.parse_bracket()?,
+                }),
+                Some('.') if self.peek_at(1) == Some('.') => {
+                    self.position += 2;
+                    let selectors = match self.peek() {
+                        Some('[') => self.parse_bracket()?,
+                        _ => [self.parse_shorthand()?].into(),
+                    };
+                    segments.push(Segment {
+                        descendant: true,
+                        selectors,
+                    });
+                }
+                Some('.') => {
+                    self.position += 1;
+                    segments.push(Segment {
+                        descendant: false,
+                        selectors: [self.parse_shorthand()?].into(),
+                    });
+                }
+                _ => {
+                    self.position = start;
+                    return Ok(segments);
+                }
+            }
+        }
+    }
+
+    fn parse_shorthand(&mut self) -> Result<Selector, PathError> {
+        if self.peek() == Some('*') {
+            self.position += 1;
+            return Ok(Selector::Wildcard);
+        }
+
+        let mut name = String::new();
+        while let Some(ch) = self.peek() {
+            let valid = ch.is_alphabetic() || ch == '_' || !ch.is_ascii() || ch.is_ascii_digit();
+            if !valid || (name.is_empty() && ch.is_ascii_digit()) {
+                break;
+            }
+            name.push(ch);
+            self.position += 1;
+        }
+
+        if name.is_empty() {
+            return Err(self.unexpected());
+        }
+        Ok(Selector::Name(name))
+    }
+
+    fn parse_bracket(&mut self) -> Result<Vec<Selector>, PathError> {
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.parse_selector()?);
+            self.skip_whitespace();
+            match self.read_char()? {
+                ',' => continue,
+                ']' => return Ok(selectors),
+                ch => return Err(PathError::UnexpectedChar(ch, self.position - 1)),
+            }
+        }
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, PathError> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.parse_string()?)),
+            Some('*') => {
+                self.position += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.position += 1;
+                Ok(Selector::Filter(self.parse_or()?))
+            }
+            Some(':') | Some('-') | Some('0'..='9') => self.parse_index_or_slice(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_int(&mut self) -> Result<Option<i64>, PathError> {
+        let start = self.position;
+        if self.peek() == Some('-') {
+            self.position += 1;
+        }
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.position += 1;
+        }
+        if self.position == start {
+            return Ok(None);
+        }
+
+        let literal: String = self.chars[start..self.position].iter().collect();
+        match literal.parse::<i64>() {
+            Ok(int) if (-MAX_SAFE_INT..=MAX_SAFE_INT).contains(&int) => Ok(Some(int)),
+            _ => Err(PathError::InvalidLiteral(literal)),
+        }
+    }
+
+    fn parse_index_or_slice(&mut self) -> Result<Selector, PathError> {
+        let start = self.parse_int()?;
+        self.skip_whitespace();
+        if self.peek() != Some(':') {
+            return start.map(Selector::Index).ok_or_else(|| self.unexpected());
+        }
+
+        self.position += 1;
+        self.skip_whitespace();
+        let end = self.parse_int()?;
+        self.skip_whitespace();
+        let mut step = None;
+        if self.peek() == Some(':') {
+            self.position += 1;
+            self.skip_whitespace();
+            step = self.parse_int()?;
+        }
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn parse_string(&mut self) -> Result<String, PathError> {
+        let quote = self.read_char()?;
+        let mut output = String::new();
+        loop {
+            match self.read_char()? {
+                ch if ch == quote => return Ok(output),
+                '\\' => output.push(self.parse_escape(quote)?),
+                ch => output.push(ch),
+            }
+        }
+    }
+
+    /// Each string escapes its own quote; every other escape is one JSON allows.
+    fn parse_escape(&mut self, quote: char) -> Result<char, PathError> {
+        match self.peek() {
+            Some(ch) if ch == quote => {
+                self.position += 1;
+                Ok(ch)
+            }
+            Some(ch @ ('\'' | '"')) => Err(PathError::InvalidLiteral(format!("\\{}", ch))),
+            _ => {
+                // The longest escape is a surrogate pair, `uXXXX\uXXXX`
+                let rest: String = self.chars[self.position..].iter().take(11).collect();
+                let (ch, length) = lexer::decode_escape(&rest).map_err(|error| match error {
+                    LexerError::InvalidTokenError(literal) => PathError::InvalidLiteral(literal),
+                    LexerError::OutOfRangeError => PathError::UnexpectedEnd,
+                })?;
+                // Escape sequences are ASCII, so bytes and chars line up
+                self.position += length;
+                Ok(ch)
+            }
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, PathError> {
+        let mut operands = [self.parse_and()?].to_vec();
+        loop {
+            self.skip_whitespace();
+            if !self.consume("||") {
+                break;
+            }
+            operands.push(self.parse_and()?);
+        }
+
+        if operands.len() == 1 {
+            return Ok(operands.remove(0));
+        }
+        Ok(Expr::Or(operands))
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, PathError> {
+        let mut operands = [self.parse_basic()?].to_vec();
+        loop {
+            self.skip_whitespace();
+            if !self.consume("&&") {
+                break;
+            }
+            operands.push(self.parse_basic()?);
+        }
+
+        if operands.len() == 1 {
+            return Ok(operands.remove(0));
+        }
+        Ok(Expr::And(operands))
+    }
+
+    fn parse_basic(&mut self) -> Result<Expr, PathError> {
+        self.skip_whitespace();
+        if self.peek() == Some('!') && self.peek_at(1) != Some('=') {
+            self.position += 1;
+            return Ok(Expr::Not(Box::new(self.parse_basic()?)));
+        }
+        if self.peek() == Some('(') {
+            self.position += 1;
+            let expr = self.parse_or()?;
+            self.skip_whitespace();
+            self.expect(')')?;
+            return Ok(expr);
+        }
+
+        let start = self.position;
+        let left = self.parse_comparable()?;
+        self.skip_whitespace();
+        let op = if self.consume("==") {
+            CompareOp::Eq
+        } else if self.consume("!=") {
+            CompareOp::Ne
+        } else if self.consume("<=") {
+            CompareOp::Le
+        } else if self.consume(">=") {
+            CompareOp::Ge
+        } else if self.consume("<") {
+            CompareOp::Lt
+        } else if self.consume(">") {
+            CompareOp::Gt
+        } else {
+            return match left {
+                Comparable::Query(query) => Ok(Expr::Exists(query)),
+                Comparable::Literal(_) => Err(self.unexpected()),
+            };
+        };
+
+        self.skip_whitespace();
+        let right_start = self.position;
+        let right = self.parse_comparable()?;
+        for (operand, position) in [(&left, start), (&right, right_start)] {
+            if matches!(operand, Comparable::Query(query) if !query.is_singular()) {
+                return Err(PathError::NonSingularQuery(position));
+            }
+        }
+        Ok(Expr::Compare(left, op, right))
+    }
+
+    fn parse_comparable(&mut self) -> Result<Comparable, PathError> {
+        match self.peek() {
+            Some(ch @ ('@' | '$')) => {
+                self.position += 1;
+                Ok(Comparable::Query(Query {
+                    relative: ch == '@',
+                    segments: self.parse_segments()?,
+                }))
+            }
+            Some('\'' | '"') => Ok(Comparable::Literal(string_value(self.parse_string()?))),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some(_) => {
+                for keyword in ["true", "false", "null"] {
+                    if self.consume(keyword) {
+                        return Ok(Comparable::Literal(Value::Token(Token {
+                            token_type: TokenType::ReservedString,
+                            literal: keyword.to_string(),
+                        })));
+                    }
+                }
+                Err(self.unexpected())
+            }
+            None => Err(PathError::UnexpectedEnd),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Comparable, PathError> {
+        let start = self.position;
+        while matches!(self.peek(), Some('-' | '+' | '.' | 'e' | 'E' | '0'..='9')) {
+            self.position += 1;
+        }
+
+        let literal: String = self.chars[start..self.position].iter().collect();
+        if literal.parse::<f64>().is_err() {
+            return Err(PathError::InvalidLiteral(literal));
+        }
+        let token_type = if literal.contains(['.', 'e', 'E']) {
+            TokenType::Float
+        } else {
+            TokenType::Int
+        };
+        Ok(Comparable::Literal(Value::Token(Token {
+            token_type,
+            literal,
+        })))
+    }
+}
+
+fn is_shorthand(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Appends the selector for the member `name` to `path`, in shorthand form when
+/// the name allows it.
+pub(crate) fn child_path(path: &str, name: &str) -> String {
+    if is_shorthand(name) {
+        return format!("{}.{}", path, name);
+    }
+    let escaped = name.replace('\\', "\\\\").replace('\'', "\\'");
+    format!("{}['{}']", path, escaped)
+}
+
+fn string_value(literal: String) -> Value {
+    Value::Token(Token {
+        token_type: TokenType::String(StringType::ComplexString),
+        literal,
+    })
+}
+
+impl Query {
+    /// Whether the query selects at most one node: only name and index
+    /// selectors, one per segment, and no descendant segments.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            !segment.descendant
+                && matches!(
+                    segment.selectors.as_slice(),
+                    [Selector::Name(_) | Selector::Index(_)]
+                )
+        })
+    }
+
+    fn evaluate<'a>(&self, current: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+        let mut nodes = [current].to_vec();
+        for segment in &self.segments {
+            let mut selected = Vec::new();
+            for node in nodes {
+                if segment.descendant {
+                    let mut descendants = Vec::new();
+                    collect_descendants(node, &mut descendants);
+                    for descendant in descendants {
+                        segment.select(descendant, root, &mut selected);
+                    }
+                } else {
+                    segment.select(node, root, &mut selected);
+                }
+            }
+            nodes = selected;
+        }
+        nodes
+    }
+}
+
+fn collect_descendants<'a>(node: &'a Value, output: &mut Vec<&'a Value>) {
+    output.push(node);
+    for child in children(node) {
+        collect_descendants(child, output);
+    }
+}
+
+fn children(node: &Value) -> Vec<&Value> {
+    match node {
+        Value::Object(object) => object.values().collect(),
+        Value::Array(array) => array.iter().collect(),
+        Value::Token(_) => Vec::new(),
+    }
+}
+
+/// Indices and slice bounds must lie within the I-JSON integer range, ±(2^53 - 1).
+const MAX_SAFE_INT: i64 = (1 << 53) - 1;
+
+fn normalize(index: i64, len: i64) -> i64 {
+    if index >= 0 {
+        index
+    } else {
+        len + index
+    }
+}
+
+impl Segment {
+    fn select<'a>(&self, node: &'a Value, root: &'a Value, output: &mut Vec<&'a Value>) {
+        for selector in &self.selectors {
+            match (selector, node) {
+                (Selector::Name(name), Value::Object(object)) => output.extend(object.get(name)),
+                (Selector::Wildcard, _) => output.extend(children(node)),
+                (Selector::Index(index), Value::Array(array)) => {
+                    let index = normalize(*index, array.len() as i64);
+                    if index >= 0 {
+                        output.extend(array.get(index as usize));
+                    }
+                }
+                (Selector::Slice(start, end, step), Value::Array(array)) => {
+                    select_slice(array, *start, *end, *step, output)
+                }
+                (Selector::Filter(expr), _) => output.extend(
+                    children(node)
+                        .into_iter()
+                        .filter(|child| expr.test(child, root)),
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn select_slice<'a>(
+    array: &'a [Value],
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+    output: &mut Vec<&'a Value>,
+) {
+    let len = array.len() as i64;
+    let step = step.unwrap_or(1);
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0), len).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len), len).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            output.push(&array[i as usize]);
+            i = i.saturating_add(step);
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(len - 1), len).clamp(-1, len - 1);
+        let lower = match end {
+            Some(end) => normalize(end, len).clamp(-1, len - 1),
+            None => -1,
+        };
+        let mut i = upper;
+        while lower < i {
+            output.push(&array[i as usize]);
+            i = i.saturating_add(step);
+        }
+    }
+}
+
+impl Expr {
+    fn test(&self, current: &Value, root: &Value) -> bool {
+        match self {
+            Expr::Or(operands) => operands.iter().any(|expr| expr.test(current, root)),
+            Expr::And(operands) => operands.iter().all(|expr| expr.test(current, root)),
+            Expr::Not(expr) => !expr.test(current, root),
+            Expr::Exists(query) => !query.resolve(current, root).is_empty(),
+            Expr::Compare(left, op, right) => {
+                let left = left.single(current, root);
+                let right = right.single(current, root);
+                match op {
+                    CompareOp::Eq => equal(left, right),
+                    CompareOp::Ne => !equal(left, right),
+                    CompareOp::Lt => less(left, right),
+                    CompareOp::Le => less(left, right) || equal(left, right),
+                    CompareOp::Gt => less(right, left),
+                    CompareOp::Ge => less(right, left) || equal(left, right),
+                }
+            }
+        }
+    }
+}
+
+impl Query {
+    fn resolve<'a>(&self, current: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+        if self.relative {
+            self.evaluate(current, root)
+        } else {
+            self.evaluate(root, root)
+        }
+    }
+}
+
+impl Comparable {
+    /// Produces the single value being compared, or `None` when the query selects nothing.
+    fn single<'a>(&'a self, current: &'a Value, root: &'a Value) -> Option<&'a Value> {
+        match self {
+            Comparable::Literal(value) => Some(value),
+            Comparable::Query(query) => {
+                let nodes = query.resolve(current, root);
+                match nodes.len() {
+                    1 => Some(nodes[0]),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+fn as_number(token: &Token) -> Option<f64> {
+    match token.token_type {
+        TokenType::Int | TokenType::Float => token.literal.parse().ok(),
+        _ => None,
+    }
+}
+
+fn compare_tokens(left: &Token, right: &Token) -> Option<Ordering> {
+    if let (Some(l), Some(r)) = (as_number(left), as_number(right)) {
+        return l.partial_cmp(&r);
+    }
+    match (&left.token_type, &right.token_type) {
+        (TokenType::String(_), TokenType::String(_)) => Some(left.literal.cmp(&right.literal)),
+        _ => None,
+    }
+}
+
+fn equal(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(l), Some(r)) => l.equivalent(r),
+        _ => false,
+    }
+}
+
+fn less(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (Some(Value::Token(l)), Some(Value::Token(r))) => {
+            let comparable = matches!(
+                (&l.token_type, &r.token_type),
+                (TokenType::String(_), TokenType::String(_))
+            ) || (as_number(l).is_some() && as_number(r).is_some());
+            comparable && compare_tokens(l, r) == Some(Ordering::Less)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{JsonPath, PathError};
+    use crate::mapper::Value;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    const JSON: &str = r#"
+    {
+        "devices": [
+            {"id": "a1", "temp": 38.5, "tags": ["indoor"]},
+            {"id": "b2", "temp": 41, "tags": []},
+            {"id": "c3", "temp": 45.25, "owner": {"id": "x"}}
+        ],
+        "limit": 40
+    }"#;
+
+    fn select(path: &str) -> Vec<String> {
+        let value: Value = JSON.parse().unwrap();
+        let nodes = value.query(path).unwrap();
+        nodes.iter().map(|node| node.to_string()).collect()
+    }
+
+    #[test]
+    pub fn test_selectors() {
+        assert_eq!(select("$.devices[1].id"), ["\"b2\""]);
+        assert_eq!(select("$['devices'][-1]['id']"), ["\"c3\""]);
+        assert_eq!(select("$.devices[*].id"), ["\"a1\"", "\"b2\"", "\"c3\""]);
+        assert_eq!(select("$.devices[::-2].id"), ["\"c3\"", "\"a1\""]);
+        assert_eq!(select("$.devices[0:2].id"), ["\"a1\"", "\"b2\""]);
+        assert_eq!(select("$.devices[0,2].temp"), ["38.5", "45.25"]);
+        assert_eq!(select("$..id"), ["\"a1\"", "\"b2\"", "\"c3\"", "\"x\""]);
+        assert_eq!(select("$.missing"), Vec::<String>::new());
+        assert_eq!(select("$.devices[1:3:9007199254740991].id"), ["\"b2\""]);
+        assert_eq!(select("$.devices[1::-9007199254740991].id"), ["\"b2\""]);
+    }
+
+    #[test]
+    pub fn test_filters() {
+        assert_eq!(select("$.devices[?(@.temp > 40)].id"), ["\"b2\"", "\"c3\""]);
+        assert_eq!(select("$.devices[?@.temp <= $.limit].id"), ["\"a1\""]);
+        assert_eq!(select("$.devices[?@.owner].id"), ["\"c3\""]);
+        assert_eq!(
+            select("$.devices[?!@.owner && @.id != 'a1'].id"),
+            ["\"b2\""]
+        );
+        assert_eq!(
+            select("$.devices[?@.temp == 41 || @.tags[0] == \"indoor\"].id"),
+            ["\"a1\"", "\"b2\""]
+        );
+    }
+
+    #[test]
+    pub fn test_parse_errors() {
+        assert_eq!(
+            JsonPath::parse("devices"),
+            Err(PathError::UnexpectedChar('d', 0))
+        );
+        assert_eq!(JsonPath::parse("$.devices["), Err(PathError::UnexpectedEnd));
+        assert_eq!(
+            JsonPath::parse("$.devices[?@.temp >]"),
+            Err(PathError::UnexpectedChar(']', 19))
+        );
+        assert!("$..devices[1:2:3]".parse::<JsonPath>().is_ok());
+        assert!(JsonPath::parse("$[9007199254740991]").is_ok());
+        assert_eq!(
+            JsonPath::parse("$[9007199254740992]"),
+            Err(PathError::InvalidLiteral("9007199254740992".to_string()))
+        );
+        assert!(JsonPath::parse("$[-9007199254740992:]").is_err());
+        assert!(JsonPath::parse("$.a").unwrap() != JsonPath::parse("$.b").unwrap());
+    }
+
+    #[test]
+    pub fn test_string_escapes() {
+        let value: Value = r#"{"😀": 1, "it's": 2, "a\"b": 3, "tab\there": 4}"#
+            .parse()
+            .unwrap();
+        let select = |path: &str| -> Vec<String> {
+            let nodes = value.query(path).unwrap();
+            nodes.iter().map(|node| node.to_string()).collect()
+        };
+        assert_eq!(select(r#"$['\uD83D\uDE00']"#), ["1"]);
+        assert_eq!(select(r#"$["\ud83d\ude00"]"#), ["1"]);
+        assert_eq!(select(r#"$['it\'s']"#), ["2"]);
+        assert_eq!(select(r#"$["a\"b"]"#), ["3"]);
+        assert_eq!(select(r#"$['tab\there']"#), ["4"]);
+
+        assert_eq!(
+            JsonPath::parse(r#"$['\q']"#),
+            Err(PathError::InvalidLiteral("\\q".to_string()))
+        );
+        assert_eq!(
+            JsonPath::parse(r#"$['a\"b']"#),
+            Err(PathError::InvalidLiteral("\\\"".to_string()))
+        );
+        assert_eq!(
+            JsonPath::parse(r#"$["it\'s"]"#),
+            Err(PathError::InvalidLiteral("\\'".to_string()))
+        );
+        assert!(JsonPath::parse(r#"$['\uD83D']"#).is_err());
+        assert!(JsonPath::parse(r#"$['\uDE00']"#).is_err());
+        assert_eq!(
+            JsonPath::parse(r#"$['\uD83D"#),
+            Err(PathError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    pub fn test_singular_comparisons() {
+        assert!(JsonPath::parse("$[?@.a[0]['b'] == $.c]").is_ok());
+        assert!(JsonPath::parse("$[?@.*]").is_ok());
+        assert_eq!(
+            JsonPath::parse("$[?@.* == 1]"),
+            Err(PathError::NonSingularQuery(3))
+        );
+        assert_eq!(
+            JsonPath::parse("$[?1 < $..a]"),
+            Err(PathError::NonSingularQuery(7))
+        );
+        assert!(JsonPath::parse("$[?@[0,1] == 1]").is_err());
+        assert!(JsonPath::parse("$[?@[0:1] == 1]").is_err());
+        assert!(JsonPath::parse("$[?@[?@.a] == 1]").is_err());
+    }
+}
@@ -4,6 +4,35 @@ use alloc::vec::Vec;
 
 const SIMPLE_ALPHA: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_";
 const DIGITS: &str = "0123456789";
+// Whitespace without a `Chars` variant of its own
+fn is_whitespace(ch: u8) -> bool {
+    ch == b'\t' || ch == b'\r'
+}
+
+// A number needs a digit after its minus sign, its dot and its exponent marker or sign
+fn is_complete_number(literal: &str) -> bool {
+    let bytes = literal.as_bytes();
+    bytes.iter().enumerate().all(|(i, ch)| {
+        let next = bytes.get(i + 1);
+        match ch {
+            b'e' | b'E' => matches!(next, Some(b'0'..=b'9' | b'+' | b'-')),
+            b'-' | b'+' | b'.' => matches!(next, Some(b'0'..=b'9')),
+            _ => true,
+        }
+    })
+}
+
+// JSON allows a zero only as the whole integer part of a number, as in `0` or `-0.5`
+fn has_leading_zero(literal: &str) -> bool {
+    let digits = literal.strip_prefix('-').unwrap_or(literal).as_bytes();
+    matches!(digits, [b'0', b'0'..=b'9', ..])
+}
+
+// The only bare words JSON knows
+fn is_literal_name(literal: &str) -> bool {
+    matches!(literal, "true" | "false" | "null")
+}
+
 //  Json tokens
 pub enum Chars {
     LBrace,
@@ -38,6 +67,7 @@ impl Chars {
         }
     }
 
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         match self {
             Chars::LBrace => "{".to_string(),
@@ -101,20 +131,29 @@ pub struct Token {
 }
 
 impl Token {
+    #[allow(clippy::should_implement_trait)]
     pub fn default() -> Token {
-        return Token {
+        Token {
             token_type: TokenType::None,
             literal: "".to_string(),
-        };
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum LexerError {
     InvalidTokenError(String),
     OutOfRangeError,
 }
 
+/// Decodes the JSON escape sequence at the start of `input`, which follows a
+/// backslash, returning the character and the number of bytes it spans.
+pub(crate) fn decode_escape(input: &str) -> Result<(char, usize), LexerError> {
+    let mut lexer = Lexer::new(input.to_string());
+    let ch = lexer.read_escape()?;
+    Ok((ch, lexer.position))
+}
+
 #[derive(Clone, Debug)]
 pub struct Lexer {
     pub input: String,
@@ -126,7 +165,7 @@ pub struct Lexer {
 
 impl Lexer {
     pub fn new(input: String) -> Lexer {
-        return Lexer {
+        Lexer {
             input,
             position: 0,
             token_list: Vec::new(),
@@ -135,7 +174,7 @@ impl Lexer {
                 token_type: TokenType::None,
                 literal: "".to_string(),
             },
-        };
+        }
     }
 
     fn read_char(&mut self) -> Result<Chars, LexerError> {
@@ -145,7 +184,64 @@ impl Lexer {
 
         let ch = self.input.as_bytes()[self.position];
         self.position += 1;
-        return Ok(Chars::from(ch));
+        Ok(Chars::from(ch))
+    }
+
+    fn read_multibyte_char(&mut self) -> Result<char, LexerError> {
+        let start = self.position - 1;
+        let ch = self
+            .input
+            .get(start..)
+            .and_then(|rest| rest.chars().next())
+            .ok_or(LexerError::OutOfRangeError)?;
+        self.position = start + ch.len_utf8();
+        Ok(ch)
+    }
+
+    fn read_hex_code(&mut self) -> Result<u32, LexerError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let ch = self.read_char()?.to_char();
+            let digit = ch
+                .to_digit(16)
+                .ok_or_else(|| LexerError::InvalidTokenError(ch.to_string()))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn read_unicode_escape(&mut self) -> Result<char, LexerError> {
+        let high = self.read_hex_code()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            // Characters outside the BMP are escaped as a UTF-16 surrogate pair
+            if self.read_char()?.to_char() != '\\' || self.read_char()?.to_char() != 'u' {
+                return Err(LexerError::InvalidTokenError(format!("\\u{:04x}", high)));
+            }
+            let low = self.read_hex_code()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(LexerError::InvalidTokenError(format!("\\u{:04x}", low)));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code)
+            .ok_or_else(|| LexerError::InvalidTokenError(format!("\\u{:04x}", code)))
+    }
+
+    fn read_escape(&mut self) -> Result<char, LexerError> {
+        let ch = self.read_char()?.to_char();
+        match ch {
+            '"' | '\\' | '/' => Ok(ch),
+            'b' => Ok('\u{8}'),
+            'f' => Ok('\u{c}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            'u' => self.read_unicode_escape(),
+            _ => Err(LexerError::InvalidTokenError(format!("\\{}", ch))),
+        }
     }
 
     fn process_start_token(&mut self) -> Result<(), LexerError> {
@@ -191,9 +287,9 @@ impl Lexer {
             Chars::MinusSign => {
                 self.current_token.token_type = TokenType::Int;
                 self.current_token.literal = ch.to_string();
-                self.token_list.push(self.current_token.clone());
             }
             Chars::Space => {}
+            Chars::Char(c) if is_whitespace(c) => {}
             Chars::Char(c) => {
                 if DIGITS.contains(c as char) {
                     self.current_token.token_type = TokenType::Int;
@@ -212,13 +308,39 @@ impl Lexer {
         Ok(())
     }
 
+    fn push_number(&mut self) -> Result<(), LexerError> {
+        let literal = &self.current_token.literal;
+        if !is_complete_number(literal) || has_leading_zero(literal) {
+            return Err(LexerError::InvalidTokenError(
+                self.current_token.literal.clone(),
+            ));
+        }
+        self.token_list.push(self.current_token.clone());
+        self.current_token = Token::default();
+        Ok(())
+    }
+
+    fn push_reserved_string(&mut self) -> Result<(), LexerError> {
+        if !is_literal_name(&self.current_token.literal) {
+            return Err(LexerError::InvalidTokenError(
+                self.current_token.literal.clone(),
+            ));
+        }
+        self.token_list.push(self.current_token.clone());
+        self.current_token = Token::default();
+        Ok(())
+    }
+
     fn process_int_token(&mut self) -> Result<(), LexerError> {
         let ch = self.read_char()?;
         match ch {
-            Chars::Char(_) => {
+            Chars::Char(c) if !is_whitespace(c) => {
                 if DIGITS.contains(ch.to_char()) {
                     self.current_token.token_type = TokenType::Int;
                     self.current_token.literal.push(ch.to_char());
+                } else if ch.to_char() == 'e' || ch.to_char() == 'E' {
+                    self.current_token.token_type = TokenType::Float;
+                    self.current_token.literal.push(ch.to_char());
                 } else {
                     return Err(LexerError::InvalidTokenError(ch.to_string()));
                 }
@@ -228,8 +350,7 @@ impl Lexer {
                 self.current_token.literal.push('.');
             }
             _ => {
-                self.token_list.push(self.current_token.clone());
-                self.current_token = Token::default();
+                self.push_number()?;
                 self.position -= 1;
             }
         }
@@ -240,18 +361,28 @@ impl Lexer {
     fn process_string_token(&mut self) -> Result<(), LexerError> {
         let ch = self.read_char()?;
         match ch {
-            Chars::Char(c) => {
-                if SIMPLE_ALPHA.contains(c as char) || DIGITS.contains(c as char) {
-                    self.current_token.literal.push(ch.to_char());
-                } else {
-                    self.current_token.token_type = TokenType::String(StringType::ComplexString);
-                    self.current_token.literal.push(ch.to_char());
-                }
+            Chars::Char(b'\\') => {
+                self.current_token.token_type = TokenType::String(StringType::ComplexString);
+                let escaped = self.read_escape()?;
+                self.current_token.literal.push(escaped);
+            }
+            Chars::Char(c) if !c.is_ascii() => {
+                self.current_token.token_type = TokenType::String(StringType::ComplexString);
+                let multibyte = self.read_multibyte_char()?;
+                self.current_token.literal.push(multibyte);
+            }
+            Chars::Char(c) if SIMPLE_ALPHA.contains(c as char) || DIGITS.contains(c as char) => {
+                self.current_token.literal.push(ch.to_char());
             }
             Chars::Quote => {
                 self.token_list.push(self.current_token.clone());
                 self.current_token = Token::default();
             }
+            // Control characters must be escaped inside strings
+            Chars::NewLine => return Err(LexerError::InvalidTokenError("\\n".to_string())),
+            Chars::Char(c) if c < 0x20 => {
+                return Err(LexerError::InvalidTokenError(format!("\\u{:04x}", c)));
+            }
             _ => {
                 self.current_token.token_type = TokenType::String(StringType::ComplexString);
                 self.current_token.literal.push(ch.to_char());
@@ -264,16 +395,14 @@ impl Lexer {
     fn process_reseved_string(&mut self) -> Result<(), LexerError> {
         let ch = self.read_char()?;
         match ch {
-            Chars::Char(c) => {
-                if SIMPLE_ALPHA.contains(c as char) || DIGITS.contains(c as char) {
-                    self.current_token.literal.push(ch.to_char());
-                } else {
-                    return Err(LexerError::InvalidTokenError(ch.to_string()));
-                }
+            Chars::Char(c) if SIMPLE_ALPHA.contains(c as char) || DIGITS.contains(c as char) => {
+                self.current_token.literal.push(ch.to_char());
+            }
+            Chars::Char(c) if !is_whitespace(c) => {
+                return Err(LexerError::InvalidTokenError(ch.to_string()));
             }
             _ => {
-                self.token_list.push(self.current_token.clone());
-                self.current_token = Token::default();
+                self.push_reserved_string()?;
                 self.position -= 1;
             }
         }
@@ -283,18 +412,27 @@ impl Lexer {
 
     fn process_float_token(&mut self) -> Result<(), LexerError> {
         let ch = self.read_char()?;
+        let after_exponent = self.current_token.literal.ends_with(['e', 'E']);
         match ch {
-            Chars::Char(_) => {
+            Chars::Char(c) if !is_whitespace(c) => {
                 if DIGITS.contains(ch.to_char()) {
                     self.current_token.token_type = TokenType::Float;
                     self.current_token.literal.push(ch.to_char());
+                } else if (ch.to_char() == 'e' || ch.to_char() == 'E')
+                    && !self.current_token.literal.contains(['e', 'E'])
+                {
+                    self.current_token.literal.push(ch.to_char());
+                } else if ch.to_char() == '+' && after_exponent {
+                    self.current_token.literal.push('+');
                 } else {
                     return Err(LexerError::InvalidTokenError(ch.to_string()));
                 }
             }
+            Chars::MinusSign if after_exponent => {
+                self.current_token.literal.push('-');
+            }
             _ => {
-                self.token_list.push(self.current_token.clone());
-                self.current_token = Token::default();
+                self.push_number()?;
                 self.position -= 1;
             }
         }
@@ -334,6 +472,132 @@ impl Lexer {
             self.process_next_token()?
         }
 
+        // A number or keyword may run up to the very end of the input
+        match self.current_token.token_type {
+            TokenType::Int | TokenType::Float => self.push_number()?,
+            TokenType::ReservedString => self.push_reserved_string()?,
+            TokenType::String(_) => return Err(LexerError::OutOfRangeError),
+            _ => {}
+        }
+
         Ok(self.token_list.clone())
     }
 }
+
+#[cfg(test)]
+pub mod test {
+    use super::{Lexer, LexerError, TokenType};
+    use alloc::string::ToString;
+
+    #[test]
+    pub fn test_incomplete_numbers() {
+        for input in [
+            "-", "1.", "1.e5", "1e", "1E", "1.0e+", "1e-", "[-]", "[1e, 2]",
+        ] {
+            let error = Lexer::new(input.to_string()).tokenize().unwrap_err();
+            assert!(
+                matches!(error, LexerError::InvalidTokenError(_)),
+                "{} lexed as a number",
+                input
+            );
+        }
+        assert!("1e".parse::<crate::mapper::Value>().is_err());
+
+        for input in ["-0", "1.5", "1e5", "1E+5", "-2.5e-3", "[1.0e+2]"] {
+            let tokens = Lexer::new(input.to_string()).tokenize().unwrap();
+            assert!(tokens
+                .iter()
+                .any(|token| matches!(token.token_type, TokenType::Int | TokenType::Float)));
+        }
+    }
+
+    #[test]
+    pub fn test_literal_names() {
+        use crate::mapper::{JsonType, Value};
+
+        for input in [
+            "True",
+            "nul",
+            "nulls",
+            "undefined",
+            "[true, NaN]",
+            "{\"a\": False}",
+        ] {
+            let error = Lexer::new(input.to_string()).tokenize().unwrap_err();
+            assert!(
+                matches!(error, LexerError::InvalidTokenError(_)),
+                "{} lexed as a word",
+                input
+            );
+        }
+        assert!("True".parse::<crate::mapper::Value>().is_err());
+        let tokens = Lexer::new("[true,false,null]".to_string())
+            .tokenize()
+            .unwrap();
+        assert_eq!(tokens.len(), 7);
+        let types: alloc::vec::Vec<_> = ["true", "false", "null"]
+            .iter()
+            .map(|input| input.parse::<Value>().unwrap().json_type())
+            .collect();
+        assert_eq!(types, [JsonType::Bool, JsonType::Bool, JsonType::Null]);
+    }
+
+    #[test]
+    pub fn test_leading_zeros() {
+        for input in ["01", "-01", "00", "00.5", "[1, 007]", "012e3"] {
+            let error = Lexer::new(input.to_string()).tokenize().unwrap_err();
+            assert!(
+                matches!(error, LexerError::InvalidTokenError(_)),
+                "{} lexed as a number",
+                input
+            );
+        }
+        assert!("01".parse::<crate::mapper::Value>().is_err());
+        for input in ["0", "-0", "0.5", "-0.5", "0e5", "10", "100.01"] {
+            assert!(
+                Lexer::new(input.to_string()).tokenize().is_ok(),
+                "{} was rejected",
+                input
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_control_characters_in_strings() {
+        for input in [
+            "\"a\nb\"",
+            "\"a\tb\"",
+            "\"\u{1}\"",
+            "[\"\r\"]",
+            "{\"\u{1f}\": 1}",
+        ] {
+            let error = Lexer::new(input.to_string()).tokenize().unwrap_err();
+            assert!(
+                matches!(error, LexerError::InvalidTokenError(_)),
+                "{:?} lexed as a string",
+                input
+            );
+        }
+        assert!("\"a\nb\"".parse::<crate::mapper::Value>().is_err());
+
+        // Escaped, they are read and written back escaped
+        let value: crate::mapper::Value = r#""a\nb\t\u0001\u001f""#.parse().unwrap();
+        assert_eq!(value.to_string(), r#""a\nb\t\u0001\u001f""#);
+    }
+
+    #[test]
+    pub fn test_whitespace_after_values() {
+        // Pretty-printed input may end a number or keyword with a tab or carriage return
+        for input in ["[1\t,2]", "[1.5\r\n]", "{\"a\":true\t}", "null\r", "-3e2\t"] {
+            assert!(
+                Lexer::new(input.to_string()).tokenize().is_ok(),
+                "{:?} was rejected",
+                input
+            );
+        }
+        let tokens = Lexer::new("[12\t,false\r]".to_string()).tokenize().unwrap();
+        let literals: alloc::vec::Vec<_> =
+            tokens.iter().map(|token| token.literal.as_str()).collect();
+        assert_eq!(literals, ["[", "12", ",", "false", "]"]);
+    }
+}
@@ -1,14 +1,23 @@
 //No Std crate
 #![no_std]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use alloc::string::String;
+use alloc::vec::Vec;
 
+pub mod diff;
+pub mod formatter;
+pub mod jsonpath;
 pub mod lexer;
 pub mod mapper;
+pub mod patch;
+pub mod pointer;
 pub mod serializer;
 
 pub use tiny_json_derive::Deserialize;
+pub use tiny_json_derive::JsonKey;
 pub use tiny_json_derive::Serialize;
 
 pub fn encode<T>(value: T) -> String
@@ -18,9 +27,63 @@ where
     serializer::encode(value)
 }
 
+pub fn encode_to<T, W>(value: T, out: &mut W) -> core::fmt::Result
+where
+    T: serializer::Serialize,
+    W: core::fmt::Write,
+{
+    serializer::encode_to(value, out)
+}
+
+pub fn encode_with<T>(
+    value: T,
+    options: &formatter::EncodeOptions,
+) -> Result<String, core::fmt::Error>
+where
+    T: serializer::Serialize,
+{
+    serializer::encode_with(value, options)
+}
+
+#[cfg(feature = "std")]
+pub fn encode_to_writer<T, W>(value: T, writer: W) -> std::io::Result<()>
+where
+    T: serializer::Serialize,
+    W: std::io::Write,
+{
+    serializer::encode_to_writer(value, writer)
+}
+
+pub fn encode_pretty<T>(
+    value: T,
+    config: &formatter::PrettyConfig,
+) -> Result<String, core::fmt::Error>
+where
+    T: serializer::Serialize,
+{
+    serializer::encode_pretty(value, config)
+}
+
 pub fn decode<T>(input_str: String) -> Result<T, serializer::DecodeError>
 where
     T: serializer::Deserialize,
 {
     serializer::decode(input_str)
 }
+
+pub fn decode_with<T>(
+    input_str: String,
+    options: &serializer::DecodeOptions,
+) -> Result<T, serializer::DecodeError>
+where
+    T: serializer::Deserialize,
+{
+    serializer::decode_with(input_str, options)
+}
+
+pub fn decode_all<T>(input_str: String) -> Result<T, Vec<serializer::DecodeError>>
+where
+    T: serializer::Deserialize,
+{
+    serializer::decode_all(input_str)
+}
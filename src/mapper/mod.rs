@@ -1,4 +1,6 @@
-use crate::lexer::{StringType, Token, TokenType};
+use crate::formatter::{self, EncodeOptions, PrettyConfig};
+use crate::lexer::{Token, TokenType};
+use crate::serializer::Coercion;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
@@ -15,10 +17,99 @@ pub enum Value {
 
 impl Display for Value {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            formatter::write_pretty(self, f, &PrettyConfig::default())
+        } else {
+            formatter::write_compact(self, f, &EncodeOptions::default())
+        }
+    }
+}
+
+/// The kinds of value JSON distinguishes, as named in decoding errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonType {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl Display for JsonType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            JsonType::Null => "null",
+            JsonType::Bool => "boolean",
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        })
+    }
+}
+
+/// The type of a scalar token.
+pub(crate) fn scalar_type(token: &Token) -> JsonType {
+    match token.token_type {
+        TokenType::String(_) => JsonType::String,
+        TokenType::Int | TokenType::Float => JsonType::Number,
+        TokenType::ReservedString if matches!(token.literal.as_str(), "true" | "false") => {
+            JsonType::Bool
+        }
+        // `null`, the only other word the lexer accepts
+        _ => JsonType::Null,
+    }
+}
+
+fn tokens_equivalent(left: &Token, right: &Token) -> bool {
+    match (&left.token_type, &right.token_type) {
+        (TokenType::String(_), TokenType::String(_)) => left.literal == right.literal,
+        (TokenType::Int | TokenType::Float, TokenType::Int | TokenType::Float) => {
+            left.literal == right.literal
+                || matches!(
+                    (left.literal.parse::<f64>(), right.literal.parse::<f64>()),
+                    (Ok(l), Ok(r)) if l == r
+                )
+        }
+        (l, r) => l == r && left.literal == right.literal,
+    }
+}
+
+impl Value {
+    pub fn null() -> Value {
+        Value::Token(Token {
+            token_type: TokenType::ReservedString,
+            literal: String::from("null"),
+        })
+    }
+
+    pub fn json_type(&self) -> JsonType {
         match self {
-            Value::Token(token) => write!(f, "{}", token.literal),
-            Value::Object(object) => write!(f, "{:?}", object),
-            Value::Array(array) => write!(f, "{:?}", array),
+            Value::Object(_) => JsonType::Object,
+            Value::Array(_) => JsonType::Array,
+            Value::Token(token) => scalar_type(token),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Token(token) if token.token_type == TokenType::ReservedString && token.literal == "null")
+    }
+
+    /// Compares two values by JSON semantics rather than by representation: strings
+    /// match on content alone and numbers on their numeric value, so `1` equals `1.0`.
+    pub fn equivalent(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Token(l), Value::Token(r)) => tokens_equivalent(l, r),
+            (Value::Array(l), Value::Array(r)) => {
+                l.len() == r.len() && l.iter().zip(r).all(|(l, r)| l.equivalent(r))
+            }
+            (Value::Object(l), Value::Object(r)) => {
+                l.len() == r.len()
+                    && l.iter()
+                        .all(|(key, l)| r.get(key).is_some_and(|r| l.equivalent(r)))
+            }
+            _ => false,
         }
     }
 }
@@ -28,9 +119,20 @@ pub enum MapperError {
     UnexpectedToken(Token),
 }
 
+// Past the last token the stream reads as `TokenType::None`, as `Token::default` does
+fn token_at(token_list: &[Token], position: usize) -> &Token {
+    static END: Token = Token {
+        token_type: TokenType::None,
+        literal: String::new(),
+    };
+    token_list.get(position).unwrap_or(&END)
+}
+
 pub struct Mapper {
     pub token_list: Vec<Token>,
     pub position: usize,
+    /// Coercion applied to the scalars read through [`crate::serializer::Deserialize::deserialize_from`].
+    pub coercion: Coercion,
 }
 
 impl Mapper {
@@ -38,105 +140,233 @@ impl Mapper {
         Mapper {
             token_list,
             position: 0,
+            coercion: Coercion::STRICT,
         }
     }
 
-    fn read_token(&mut self) -> Token {
-        let token = self.token_list[self.position].clone();
+    fn read_token(&mut self) -> &Token {
         self.position += 1;
-        token
+        token_at(&self.token_list, self.position - 1)
     }
 
-    fn peek_token(&mut self) -> Token {
-        self.token_list[self.position].clone()
+    fn peek_token(&self) -> &Token {
+        token_at(&self.token_list, self.position)
     }
 
-    fn expect(&mut self, token_type: TokenType) -> Result<Token, MapperError> {
+    fn expect(&mut self, token_type: TokenType) -> Result<(), MapperError> {
         let token = self.read_token();
         if token.token_type != token_type {
-            return Err(MapperError::UnexpectedToken(token));
+            return Err(MapperError::UnexpectedToken(token.clone()));
+        }
+        Ok(())
+    }
+
+    /// Parses the next value of the stream into a [`Value`].
+    pub fn parse_element(&mut self) -> Result<Value, MapperError> {
+        let token = self.peek_token();
+        match token.token_type {
+            TokenType::LBrace => Ok(Value::Object(self.parse_object()?)),
+            TokenType::LBracket => Ok(Value::Array(self.parse_array()?)),
+            TokenType::String(_)
+            | TokenType::Int
+            | TokenType::Float
+            | TokenType::ReservedString => Ok(Value::Token(self.read_token().clone())),
+            _ => Err(MapperError::UnexpectedToken(token.clone())),
         }
-        Ok(token)
     }
 
     fn parse_array(&mut self) -> Result<Vec<Value>, MapperError> {
         let mut array = Vec::new();
         self.expect(TokenType::LBracket)?; // [
-        loop {
-            let token = self.peek_token();
-            if token.token_type == TokenType::RBracket {
-                break;
-            }
+        if self.peek_token().token_type == TokenType::RBracket {
+            self.read_token();
+            return Ok(array);
+        }
 
-            if token.token_type == TokenType::LBrace {
-                let object = self.parse_object()?;
-                array.push(Value::Object(object));
-            } else {
-                let token = self.read_token();
-                array.push(Value::Token(token));
-            }
+        loop {
+            array.push(self.parse_element()?);
 
             let token = self.read_token();
             match token.token_type {
                 TokenType::Comma => continue,
                 TokenType::RBracket => break,
-                _ => return Err(MapperError::UnexpectedToken(token)),
+                _ => return Err(MapperError::UnexpectedToken(token.clone())),
             }
         }
         Ok(array)
     }
 
     fn parse_value(&mut self) -> Result<(String, Value), MapperError> {
-        let key_token = self.expect(TokenType::String(StringType::SimpleString))?;
+        let key_token = self.read_token();
+        if !matches!(key_token.token_type, TokenType::String(_)) {
+            return Err(MapperError::UnexpectedToken(key_token.clone()));
+        }
+        let key = key_token.literal.clone();
         self.expect(TokenType::Colon)?;
-        let value_token = self.read_token();
-        return match value_token.token_type {
-            TokenType::String(_) => {
-                let value = Value::Token(value_token);
-                Ok((key_token.literal, value))
-            }
-            TokenType::Int | TokenType::Float | TokenType::ReservedString => {
-                let value = Value::Token(value_token);
-                Ok((key_token.literal, value))
-            }
-            TokenType::LBrace => {
-                self.position -= 1;
-                let value = Value::Object(self.parse_object()?);
-                Ok((key_token.literal, value))
-            }
-            TokenType::LBracket => {
-                self.position -= 1;
-                let value = Value::Array(self.parse_array()?);
-                Ok((key_token.literal, value))
-            }
-            _ => Err(MapperError::UnexpectedToken(value_token)),
-        };
+        let value = self.parse_element()?;
+        Ok((key, value))
     }
 
     pub fn parse_object(&mut self) -> Result<Object, MapperError> {
         let mut object = BTreeMap::new();
         self.expect(TokenType::LBrace)?;
-        loop {
-            let token = self.peek_token();
-            if token.token_type == TokenType::RBrace {
-                break;
-            }
+        if self.peek_token().token_type == TokenType::RBrace {
+            self.read_token();
+            return Ok(object);
+        }
 
+        loop {
             let (key, value) = self.parse_value()?;
             object.insert(key, value);
             let token = self.read_token();
             match token.token_type {
                 TokenType::Comma => continue,
                 TokenType::RBrace => break,
-                _ => return Err(MapperError::UnexpectedToken(token)),
+                _ => return Err(MapperError::UnexpectedToken(token.clone())),
             }
         }
         Ok(object)
     }
+
+    /// Parses a complete JSON document of any kind, rejecting trailing tokens.
+    pub fn parse(&mut self) -> Result<Value, MapperError> {
+        let value = self.parse_element()?;
+        self.finish()?;
+        Ok(value)
+    }
+
+    /// Checks that the whole token stream has been consumed.
+    pub fn finish(&mut self) -> Result<(), MapperError> {
+        self.expect(TokenType::None)?;
+        Ok(())
+    }
+
+    fn previous_token_type(&self) -> Option<&TokenType> {
+        self.position
+            .checked_sub(1)
+            .and_then(|previous| self.token_list.get(previous))
+            .map(|token| &token.token_type)
+    }
+
+    /// Consumes the opening brace of an object, whose members are then read
+    /// with [`Mapper::next_key`].
+    pub fn begin_object(&mut self) -> Result<(), MapperError> {
+        self.expect(TokenType::LBrace)?;
+        Ok(())
+    }
+
+    /// Reads the key of the next object member along with its colon, leaving the
+    /// stream at the member's value. Returns `None` once the closing brace is consumed.
+    pub fn next_key(&mut self) -> Result<Option<String>, MapperError> {
+        let first = self.previous_token_type() == Some(&TokenType::LBrace);
+        let token = self.peek_token();
+        match (first, &token.token_type) {
+            (_, TokenType::RBrace) => {
+                self.position += 1;
+                return Ok(None);
+            }
+            // Any member but the first is preceded by a comma
+            (true, _) => {}
+            (_, TokenType::Comma) => self.position += 1,
+            _ => return Err(MapperError::UnexpectedToken(token.clone())),
+        }
+
+        let token = self.read_token();
+        if !matches!(token.token_type, TokenType::String(_)) {
+            return Err(MapperError::UnexpectedToken(token.clone()));
+        }
+        let key = token.literal.clone();
+        self.expect(TokenType::Colon)?;
+        Ok(Some(key))
+    }
+
+    /// Consumes the opening bracket of an array, whose elements are then read
+    /// after each call to [`Mapper::next_element`].
+    pub fn begin_array(&mut self) -> Result<(), MapperError> {
+        self.expect(TokenType::LBracket)?;
+        Ok(())
+    }
+
+    /// Moves the stream to the next array element, returning `false` once the
+    /// closing bracket is consumed.
+    pub fn next_element(&mut self) -> Result<bool, MapperError> {
+        let first = self.previous_token_type() == Some(&TokenType::LBracket);
+        let token = self.peek_token();
+        match token.token_type {
+            TokenType::RBracket => {
+                self.read_token();
+                Ok(false)
+            }
+            _ if first => Ok(true),
+            TokenType::Comma => {
+                self.read_token();
+                Ok(true)
+            }
+            _ => Err(MapperError::UnexpectedToken(token.clone())),
+        }
+    }
+
+    /// The JSON type of the next value of the stream, if a value comes next.
+    pub fn next_type(&self) -> Option<JsonType> {
+        let token = self.peek_token();
+        match token.token_type {
+            TokenType::LBrace => Some(JsonType::Object),
+            TokenType::LBracket => Some(JsonType::Array),
+            TokenType::String(_)
+            | TokenType::Int
+            | TokenType::Float
+            | TokenType::ReservedString => Some(scalar_type(token)),
+            _ => None,
+        }
+    }
+
+    /// Consumes the next value of the stream if it is a scalar, borrowing its token
+    /// instead of building a [`Value`].
+    pub fn next_scalar(&mut self) -> Option<&Token> {
+        match self.next_type() {
+            Some(JsonType::Object | JsonType::Array) | None => None,
+            Some(_) => Some(self.read_token()),
+        }
+    }
+
+    /// Consumes the next value of the stream if it is `null`, returning whether it was.
+    pub fn next_null(&mut self) -> bool {
+        let token = self.peek_token();
+        let is_null = token.token_type == TokenType::ReservedString && token.literal == "null";
+        if is_null {
+            self.position += 1;
+        }
+        is_null
+    }
+
+    /// Skips over the next value of the stream without building it, checking its
+    /// syntax as [`Mapper::parse_element`] would.
+    pub fn skip_element(&mut self) -> Result<(), MapperError> {
+        match self.next_type() {
+            Some(JsonType::Object) => {
+                self.begin_object()?;
+                while self.next_key()?.is_some() {
+                    self.skip_element()?;
+                }
+            }
+            Some(JsonType::Array) => {
+                self.begin_array()?;
+                while self.next_element()? {
+                    self.skip_element()?;
+                }
+            }
+            Some(_) => {
+                self.read_token();
+            }
+            None => return Err(MapperError::UnexpectedToken(self.read_token().clone())),
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 pub mod test {
+    use alloc::format;
     use alloc::string::ToString;
 
     #[test]
@@ -145,7 +375,7 @@ pub mod test {
         {
             "name": "John",
             "age": 30,
-            "isActive": True,
+            "isActive": true,
             "cars": [
                 {
                     "name": "Ford",
@@ -163,9 +393,9 @@ pub mod test {
         let token_list = crate::lexer::Lexer::new(input).tokenize().unwrap();
         let mut mapper = crate::mapper::Mapper::new(token_list);
         let object = mapper.parse_object().unwrap();
-        assert_eq!(object["name"].to_string(), "John");
+        assert_eq!(object["name"].to_string(), "\"John\"");
         assert_eq!(object["age"].to_string(), "30");
-        assert_eq!(object["isActive"].to_string(), "True");
+        assert_eq!(object["isActive"].to_string(), "true");
 
         let cars = match object["cars"] {
             crate::mapper::Value::Array(ref cars) => cars,
@@ -182,9 +412,53 @@ pub mod test {
             _ => panic!("Expected object"),
         };
 
-        assert_eq!(car1["name"].to_string(), "Ford");
-        assert_eq!(car1["plate"].to_string(), "20-13f");
-        assert_eq!(car2["name"].to_string(), "Fiat");
-        assert_eq!(car2["plate"].to_string(), "20-13f");
+        assert_eq!(car1["name"].to_string(), "\"Ford\"");
+        assert_eq!(car1["plate"].to_string(), "\"20-13f\"");
+        assert_eq!(car2["name"].to_string(), "\"Fiat\"");
+        assert_eq!(car2["plate"].to_string(), "\"20-13f\"");
+    }
+
+    #[test]
+    pub fn test_next_scalar() {
+        use crate::lexer::TokenType;
+        use crate::mapper::JsonType;
+
+        let token_list = crate::lexer::Lexer::new(r#"[7, {"a": 1}]"#.to_string())
+            .tokenize()
+            .unwrap();
+        let mut mapper = crate::mapper::Mapper::new(token_list);
+        mapper.begin_array().unwrap();
+        assert!(mapper.next_element().unwrap());
+        let token = mapper.next_scalar().unwrap();
+        assert_eq!(
+            (&token.token_type, token.literal.as_str()),
+            (&TokenType::Int, "7")
+        );
+        assert!(mapper.next_element().unwrap());
+        // Objects and arrays stay in the stream
+        assert!(mapper.next_scalar().is_none());
+        assert_eq!(mapper.next_type(), Some(JsonType::Object));
+        mapper.skip_element().unwrap();
+        assert!(!mapper.next_element().unwrap());
+        assert!(mapper.next_scalar().is_none());
+        mapper.finish().unwrap();
+    }
+
+    #[test]
+    pub fn test_display_json() {
+        let input = r#"{"list": [[], {}, [1, -2.5e3]], "text": "say \"hi\"\n", "n": null}"#;
+        let token_list = crate::lexer::Lexer::new(input.to_string())
+            .tokenize()
+            .unwrap();
+        let value = crate::mapper::Mapper::new(token_list).parse().unwrap();
+
+        assert_eq!(
+            value.to_string(),
+            r#"{"list":[[],{},[1,-2.5e3]],"n":null,"text":"say \"hi\"\n"}"#
+        );
+        assert_eq!(
+            format!("{:#}", value),
+            "{\n  \"list\": [\n    [],\n    {},\n    [\n      1,\n      -2.5e3\n    ]\n  ],\n  \"n\": null,\n  \"text\": \"say \\\"hi\\\"\\n\"\n}"
+        );
     }
 }
@@ -0,0 +1,102 @@
+use crate::mapper::{Object, Value};
+
+/// Applies an RFC 7386 merge patch to `target`. Members of `patch` set to `null`
+/// are deleted from `target`, objects are merged recursively and any other value
+/// replaces what was there.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        _ => {
+            *target = patch.clone();
+            return;
+        }
+    };
+
+    if !matches!(target, Value::Object(_)) {
+        *target = Value::Object(Object::new());
+    }
+    if let Value::Object(object) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                object.remove(key);
+            } else {
+                let member = object.entry(key.clone()).or_insert_with(Value::null);
+                merge_patch(member, value);
+            }
+        }
+    }
+}
+
+/// Computes the merge patch that turns `from` into `to`.
+///
+/// Merge patches cannot set a member to `null`, so `null` members of `to` that
+/// are missing or different in `from` are deleted by the patch instead.
+pub fn generate(from: &Value, to: &Value) -> Value {
+    let (from, to) = match (from, to) {
+        (Value::Object(from), Value::Object(to)) => (from, to),
+        _ => return to.clone(),
+    };
+
+    let mut patch = Object::new();
+    for key in from.keys() {
+        if !to.contains_key(key) {
+            patch.insert(key.clone(), Value::null());
+        }
+    }
+    for (key, new_value) in to {
+        match from.get(key) {
+            Some(old_value) if old_value.equivalent(new_value) => {}
+            Some(old_value @ Value::Object(_)) if matches!(new_value, Value::Object(_)) => {
+                patch.insert(key.clone(), generate(old_value, new_value));
+            }
+            _ => {
+                patch.insert(key.clone(), new_value.clone());
+            }
+        }
+    }
+    Value::Object(patch)
+}
+
+#[cfg(test)]
+pub mod test {
+    use crate::mapper::Value;
+    use alloc::string::ToString;
+
+    #[test]
+    pub fn test_merge_patch() {
+        let mut config: Value = r#"{"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"}, "tags": ["example", "sample"], "content": "This will be unchanged"}"#
+            .parse()
+            .unwrap();
+        let patch: Value = r#"{"title": "Hello!", "phoneNumber": "+01-123-456-7890", "author": {"familyName": null}, "tags": ["example"]}"#
+            .parse()
+            .unwrap();
+
+        super::merge_patch(&mut config, &patch);
+        assert_eq!(
+            config.to_string(),
+            r#"{"author":{"givenName":"John"},"content":"This will be unchanged","phoneNumber":"+01-123-456-7890","tags":["example"],"title":"Hello!"}"#
+        );
+
+        let mut scalar: Value = "[1]".parse().unwrap();
+        super::merge_patch(
+            &mut scalar,
+            &r#"{"a": {"b": null, "c": 1}}"#.parse().unwrap(),
+        );
+        assert_eq!(scalar.to_string(), r#"{"a":{"c":1}}"#);
+    }
+
+    #[test]
+    pub fn test_generate() {
+        let from: Value = r#"{"a": 1, "b": {"c": [1], "d": "x"}, "e": true}"#.parse().unwrap();
+        let to: Value = r#"{"a": 1.0, "b": {"c": [1, 2], "d": "x"}, "f": {}}"#
+            .parse()
+            .unwrap();
+
+        let patch = super::generate(&from, &to);
+        assert_eq!(patch.to_string(), r#"{"b":{"c":[1,2]},"e":null,"f":{}}"#);
+
+        let mut patched = from.clone();
+        super::merge_patch(&mut patched, &patch);
+        assert!(patched.equivalent(&to));
+    }
+}
@@ -0,0 +1,391 @@
+use crate::lexer::{StringType, Token, TokenType};
+use crate::mapper::Value;
+use crate::pointer::{self, PointerError};
+use crate::serializer;
+use crate::serializer::DecodeError;
+use crate::Deserialize;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+pub mod merge;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Add,
+    Remove,
+    Replace,
+    Move,
+    Copy,
+    Test,
+}
+
+impl Op {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Op::Add => "add",
+            Op::Remove => "remove",
+            Op::Replace => "replace",
+            Op::Move => "move",
+            Op::Copy => "copy",
+            Op::Test => "test",
+        }
+    }
+}
+
+impl serializer::Serialize for Op {
+    fn serialize(&self) -> Value {
+        Value::Token(Token {
+            token_type: TokenType::String(StringType::SimpleString),
+            literal: self.name().to_string(),
+        })
+    }
+}
+
+impl serializer::Deserialize for Op {
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        let name: String = serializer::Deserialize::deserialize(value)?;
+        match name.as_str() {
+            "add" => Ok(Op::Add),
+            "remove" => Ok(Op::Remove),
+            "replace" => Ok(Op::Replace),
+            "move" => Ok(Op::Move),
+            "copy" => Ok(Op::Copy),
+            "test" => Ok(Op::Test),
+            _ => Err(DecodeError::invalid_value::<Op>(&name)),
+        }
+    }
+}
+
+/// A single RFC 6902 operation. A patch document is a `Vec<Operation>` and can be
+/// read and written with [`crate::decode`] and [`crate::encode`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Operation {
+    pub op: Op,
+    pub path: String,
+    pub from: Option<String>,
+    pub value: Option<Value>,
+}
+
+impl Operation {
+    pub fn add(path: String, value: Value) -> Operation {
+        Operation {
+            op: Op::Add,
+            path,
+            from: None,
+            value: Some(value),
+        }
+    }
+
+    pub fn remove(path: String) -> Operation {
+        Operation {
+            op: Op::Remove,
+            path,
+            from: None,
+            value: None,
+        }
+    }
+
+    pub fn replace(path: String, value: Value) -> Operation {
+        Operation {
+            op: Op::Replace,
+            path,
+            from: None,
+            value: Some(value),
+        }
+    }
+
+    pub fn move_from(from: String, path: String) -> Operation {
+        Operation {
+            op: Op::Move,
+            path,
+            from: Some(from),
+            value: None,
+        }
+    }
+
+    pub fn copy_from(from: String, path: String) -> Operation {
+        Operation {
+            op: Op::Copy,
+            path,
+            from: Some(from),
+            value: None,
+        }
+    }
+
+    pub fn test(path: String, value: Value) -> Operation {
+        Operation {
+            op: Op::Test,
+            path,
+            from: None,
+            value: Some(value),
+        }
+    }
+}
+
+/// Absent members are left out rather than written as `null`, so that a
+/// `test` or `add` of `null` stays distinct from an operation without a value.
+impl serializer::Serialize for Operation {
+    fn serialize(&self) -> Value {
+        let mut members = BTreeMap::new();
+        members.insert("op".to_string(), self.op.serialize());
+        members.insert("path".to_string(), self.path.serialize());
+        if let Some(from) = &self.from {
+            members.insert("from".to_string(), from.serialize());
+        }
+        if let Some(value) = &self.value {
+            members.insert("value".to_string(), value.clone());
+        }
+        Value::Object(members)
+    }
+}
+
+impl serializer::SerializeObject for Operation {}
+
+/// Errors carry the index of the operation that failed within the patch.
+#[derive(Debug, PartialEq)]
+pub enum PatchError {
+    Pointer(usize, PointerError),
+    MissingValue(usize),
+    MissingFrom(usize),
+    InvalidMove(usize),
+    TestFailed(usize),
+}
+
+fn apply_operation(
+    document: &mut Value,
+    operation: &Operation,
+    index: usize,
+) -> Result<(), PatchError> {
+    let pointer_error = |error| PatchError::Pointer(index, error);
+    let value = || {
+        operation
+            .value
+            .clone()
+            .ok_or(PatchError::MissingValue(index))
+    };
+    let from = || {
+        operation
+            .from
+            .as_deref()
+            .ok_or(PatchError::MissingFrom(index))
+    };
+
+    match operation.op {
+        Op::Add => {
+            document
+                .insert(&operation.path, value()?)
+                .map_err(pointer_error)?;
+        }
+        Op::Remove => {
+            document.remove(&operation.path).map_err(pointer_error)?;
+        }
+        Op::Replace => {
+            *document
+                .pointer_mut(&operation.path)
+                .map_err(pointer_error)? = value()?;
+        }
+        Op::Move => {
+            let from = from()?;
+            let into_child =
+                operation.path.starts_with(from) && operation.path[from.len()..].starts_with('/');
+            if into_child {
+                return Err(PatchError::InvalidMove(index));
+            }
+            let moved = document.remove(from).map_err(pointer_error)?;
+            document
+                .insert(&operation.path, moved)
+                .map_err(pointer_error)?;
+        }
+        Op::Copy => {
+            let copied = document.pointer(from()?).map_err(pointer_error)?.clone();
+            document
+                .insert(&operation.path, copied)
+                .map_err(pointer_error)?;
+        }
+        Op::Test => {
+            let actual = document.pointer(&operation.path).map_err(pointer_error)?;
+            if !actual.equivalent(&value()?) {
+                return Err(PatchError::TestFailed(index));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies every operation of `patch` to `target`. Operations run against a copy
+/// of the document, so `target` is left untouched if any of them fails.
+pub fn apply(target: &mut Value, patch: &[Operation]) -> Result<(), PatchError> {
+    let mut document = target.clone();
+    for (index, operation) in patch.iter().enumerate() {
+        apply_operation(&mut document, operation, index)?;
+    }
+    *target = document;
+    Ok(())
+}
+
+fn generate_into(from: &Value, to: &Value, path: &str, patch: &mut Vec<Operation>) {
+    match (from, to) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let child = format!("{}/{}", path, pointer::escape(key));
+                match new.get(key) {
+                    Some(new_value) => generate_into(old_value, new_value, &child, patch),
+                    None => patch.push(Operation::remove(child)),
+                }
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    let child = format!("{}/{}", path, pointer::escape(key));
+                    patch.push(Operation::add(child, new_value.clone()));
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            let common = old.len().min(new.len());
+            for i in 0..common {
+                generate_into(&old[i], &new[i], &format!("{}/{}", path, i), patch);
+            }
+            for (i, new_value) in new.iter().enumerate().skip(common) {
+                patch.push(Operation::add(format!("{}/{}", path, i), new_value.clone()));
+            }
+            for i in (common..old.len()).rev() {
+                patch.push(Operation::remove(format!("{}/{}", path, i)));
+            }
+        }
+        _ => {
+            if !from.equivalent(to) {
+                patch.push(Operation::replace(path.to_string(), to.clone()));
+            }
+        }
+    }
+}
+
+/// Computes a patch that turns `from` into `to` when applied.
+pub fn generate(from: &Value, to: &Value) -> Vec<Operation> {
+    let mut patch = Vec::new();
+    generate_into(from, to, "", &mut patch);
+    patch
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{Op, Operation, PatchError};
+    use crate::mapper::Value;
+    use crate::pointer::PointerError;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    #[test]
+    pub fn test_apply() {
+        const PATCH: &str = r#"[
+            {"op": "test", "path": "/state/on", "value": true},
+            {"op": "replace", "path": "/state/level", "value": 80},
+            {"op": "add", "path": "/tags/1", "value": "dimmable"},
+            {"op": "copy", "from": "/state/level", "path": "/last"},
+            {"op": "move", "from": "/name", "path": "/label"},
+            {"op": "remove", "path": "/tags/0"}
+        ]"#;
+
+        let mut device: Value =
+            r#"{"name": "lamp", "state": {"on": true, "level": 20}, "tags": ["a", "b"]}"#
+                .parse()
+                .unwrap();
+        let patch: Vec<Operation> = crate::decode(PATCH.to_string()).unwrap();
+        super::apply(&mut device, &patch).unwrap();
+
+        assert_eq!(
+            device.to_string(),
+            r#"{"label":"lamp","last":80,"state":{"level":80,"on":true},"tags":["dimmable","b"]}"#
+        );
+    }
+
+    #[test]
+    pub fn test_apply_is_atomic() {
+        let mut device: Value = r#"{"level": 1.0}"#.parse().unwrap();
+        let patch = [
+            Operation::replace("/level".to_string(), "2".parse().unwrap()),
+            Operation {
+                op: Op::Test,
+                path: "/level".to_string(),
+                from: None,
+                value: Some("1".parse().unwrap()),
+            },
+            Operation::remove("/missing".to_string()),
+        ];
+
+        assert_eq!(
+            super::apply(&mut device, &patch),
+            Err(PatchError::TestFailed(1))
+        );
+        assert_eq!(
+            super::apply(&mut device, &patch[2..]),
+            Err(PatchError::Pointer(
+                0,
+                PointerError::NotFound("/missing".to_string())
+            ))
+        );
+        assert_eq!(device.to_string(), r#"{"level":1.0}"#);
+    }
+
+    #[test]
+    pub fn test_generate() {
+        let from: Value = r#"{"a": 1, "b": [1, 2, 3], "c/d": {"x": "y"}, "e": null}"#
+            .parse()
+            .unwrap();
+        let to: Value = r#"{"a": 1.0, "b": [1, 5], "c/d": {"x": "y", "z": []}, "f": false}"#
+            .parse()
+            .unwrap();
+
+        let patch = super::generate(&from, &to);
+        let paths: Vec<String> = patch
+            .iter()
+            .map(|op| [op.op.name(), " ", &op.path].concat())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "replace /b/1",
+                "remove /b/2",
+                "add /c~1d/z",
+                "remove /e",
+                "add /f"
+            ]
+        );
+
+        let mut patched = from.clone();
+        super::apply(&mut patched, &patch).unwrap();
+        assert!(patched.equivalent(&to));
+
+        let encoded = crate::encode(patch.clone());
+        assert!(encoded.starts_with(
+            r#"[{"op":"replace","path":"/b/1","value":5},{"op":"remove","path":"/b/2"}"#
+        ));
+        let decoded: Vec<Operation> = crate::decode(encoded).unwrap();
+        assert_eq!(decoded, patch);
+
+        let null_value: Vec<Operation> =
+            crate::decode(r#"[{"op": "add", "path": "/e", "value": null}]"#.to_string()).unwrap();
+        assert_eq!(null_value[0].value, Some(Value::null()));
+        assert_eq!(
+            crate::encode(null_value),
+            r#"[{"op":"add","path":"/e","value":null}]"#
+        );
+
+        let one: Value = "1".parse().unwrap();
+        let moved = alloc::vec![
+            Operation::copy_from("/a".to_string(), "/g".to_string()),
+            Operation::move_from("/g".to_string(), "/h".to_string()),
+            Operation::test("/h".to_string(), one.clone()),
+        ];
+        assert_eq!(
+            crate::encode(moved.clone()),
+            r#"[{"from":"/a","op":"copy","path":"/g"},{"from":"/g","op":"move","path":"/h"},{"op":"test","path":"/h","value":1}]"#
+        );
+        let mut document = from.clone();
+        super::apply(&mut document, &moved).unwrap();
+        assert_eq!(document.pointer("/h"), Ok(&one));
+        assert!(document.pointer("/g").is_err());
+    }
+}
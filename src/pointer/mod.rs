@@ -0,0 +1,257 @@
+use crate::mapper::Value;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Errors carry the pointer prefix up to and including the segment that failed,
+/// e.g. `/cars/5` when `cars` only holds two elements.
+#[derive(Debug, PartialEq)]
+pub enum PointerError {
+    InvalidPointer(String),
+    InvalidEscape(String),
+    InvalidIndex(String),
+    NotFound(String),
+    NotContainer(String),
+}
+
+/// Escapes a single reference token, `~` as `~0` and `/` as `~1`.
+pub fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(token: &str, pointer: &str, depth: usize) -> Result<String, PointerError> {
+    let mut output = String::new();
+    let mut chars = token.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '~' {
+            output.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('0') => output.push('~'),
+            Some('1') => output.push('/'),
+            _ => return Err(PointerError::InvalidEscape(prefix(pointer, depth))),
+        }
+    }
+    Ok(output)
+}
+
+/// Returns the part of `pointer` covering the first `depth + 1` segments.
+fn prefix(pointer: &str, depth: usize) -> String {
+    match pointer.match_indices('/').nth(depth + 1) {
+        Some((end, _)) => pointer[..end].to_string(),
+        None => pointer.to_string(),
+    }
+}
+
+pub(crate) fn parse(pointer: &str) -> Result<Vec<String>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !pointer.starts_with('/') {
+        return Err(PointerError::InvalidPointer(pointer.to_string()));
+    }
+
+    pointer[1..]
+        .split('/')
+        .enumerate()
+        .map(|(depth, token)| unescape(token, pointer, depth))
+        .collect()
+}
+
+fn parse_index(token: &str, pointer: &str, depth: usize) -> Result<usize, PointerError> {
+    let valid = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    if !valid {
+        return Err(PointerError::InvalidIndex(prefix(pointer, depth)));
+    }
+    token
+        .parse()
+        .map_err(|_| PointerError::InvalidIndex(prefix(pointer, depth)))
+}
+
+fn step<'a>(
+    value: &'a Value,
+    token: &str,
+    pointer: &str,
+    depth: usize,
+) -> Result<&'a Value, PointerError> {
+    let next = match value {
+        Value::Object(object) => object.get(token),
+        Value::Array(_) if token == "-" => None,
+        Value::Array(array) => array.get(parse_index(token, pointer, depth)?),
+        Value::Token(_) => return Err(PointerError::NotContainer(prefix(pointer, depth))),
+    };
+    next.ok_or_else(|| PointerError::NotFound(prefix(pointer, depth)))
+}
+
+fn step_mut<'a>(
+    value: &'a mut Value,
+    token: &str,
+    pointer: &str,
+    depth: usize,
+) -> Result<&'a mut Value, PointerError> {
+    let next = match value {
+        Value::Object(object) => object.get_mut(token),
+        Value::Array(_) if token == "-" => None,
+        Value::Array(array) => array.get_mut(parse_index(token, pointer, depth)?),
+        Value::Token(_) => return Err(PointerError::NotContainer(prefix(pointer, depth))),
+    };
+    next.ok_or_else(|| PointerError::NotFound(prefix(pointer, depth)))
+}
+
+impl Value {
+    /// Looks up the value referenced by an RFC 6901 JSON Pointer such as `/cars/1/plate`.
+    pub fn pointer(&self, pointer: &str) -> Result<&Value, PointerError> {
+        let tokens = parse(pointer)?;
+        let mut current = self;
+        for (depth, token) in tokens.iter().enumerate() {
+            current = step(current, token, pointer, depth)?;
+        }
+        Ok(current)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut Value, PointerError> {
+        let tokens = parse(pointer)?;
+        let mut current = self;
+        for (depth, token) in tokens.iter().enumerate() {
+            current = step_mut(current, token, pointer, depth)?;
+        }
+        Ok(current)
+    }
+
+    /// Inserts `value` at `pointer`, following the semantics of the JSON Patch `add`
+    /// operation: object members are created or replaced, array elements are shifted
+    /// to make room and `-` appends. Returns the replaced member, if any.
+    pub fn insert(&mut self, pointer: &str, value: Value) -> Result<Option<Value>, PointerError> {
+        let mut tokens = parse(pointer)?;
+        let last = match tokens.pop() {
+            None => return Ok(Some(core::mem::replace(self, value))),
+            Some(last) => last,
+        };
+
+        let depth = tokens.len();
+        let mut parent = self;
+        for (depth, token) in tokens.iter().enumerate() {
+            parent = step_mut(parent, token, pointer, depth)?;
+        }
+
+        match parent {
+            Value::Object(object) => Ok(object.insert(last, value)),
+            Value::Array(array) if last == "-" => {
+                array.push(value);
+                Ok(None)
+            }
+            Value::Array(array) => {
+                let index = parse_index(&last, pointer, depth)?;
+                if index > array.len() {
+                    return Err(PointerError::NotFound(prefix(pointer, depth)));
+                }
+                array.insert(index, value);
+                Ok(None)
+            }
+            Value::Token(_) => Err(PointerError::NotContainer(prefix(pointer, depth))),
+        }
+    }
+
+    /// Removes and returns the value at `pointer`. The whole document cannot be removed.
+    pub fn remove(&mut self, pointer: &str) -> Result<Value, PointerError> {
+        let mut tokens = parse(pointer)?;
+        let last = match tokens.pop() {
+            None => return Err(PointerError::InvalidPointer(pointer.to_string())),
+            Some(last) => last,
+        };
+
+        let depth = tokens.len();
+        let mut parent = self;
+        for (depth, token) in tokens.iter().enumerate() {
+            parent = step_mut(parent, token, pointer, depth)?;
+        }
+
+        let removed = match parent {
+            Value::Object(object) => object.remove(&last),
+            Value::Array(_) if last == "-" => None,
+            Value::Array(array) => {
+                let index = parse_index(&last, pointer, depth)?;
+                if index < array.len() {
+                    Some(array.remove(index))
+                } else {
+                    None
+                }
+            }
+            Value::Token(_) => return Err(PointerError::NotContainer(prefix(pointer, depth))),
+        };
+        removed.ok_or_else(|| PointerError::NotFound(prefix(pointer, depth)))
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::PointerError;
+    use crate::mapper::Value;
+    use alloc::string::ToString;
+
+    const JSON: &str = r#"
+    {
+        "cars": [
+            {"name": "Ford", "plate": "20-13f"},
+            {"name": "Fiat", "plate": "30-14g"}
+        ],
+        "a/b": {"m~n": 8}
+    }"#;
+
+    #[test]
+    pub fn test_pointer() {
+        let value: Value = JSON.parse().unwrap();
+        assert_eq!(value.pointer("").unwrap(), &value);
+        assert_eq!(
+            value.pointer("/cars/1/plate").unwrap().to_string(),
+            "\"30-14g\""
+        );
+        assert_eq!(value.pointer("/a~1b/m~0n").unwrap().to_string(), "8");
+
+        assert_eq!(
+            value.pointer("/cars/5/plate"),
+            Err(PointerError::NotFound("/cars/5".to_string()))
+        );
+        assert_eq!(
+            value.pointer("/cars/01"),
+            Err(PointerError::InvalidIndex("/cars/01".to_string()))
+        );
+        assert_eq!(
+            value.pointer("/cars/0/name/x"),
+            Err(PointerError::NotContainer("/cars/0/name/x".to_string()))
+        );
+        assert_eq!(
+            value.pointer("/a~2b"),
+            Err(PointerError::InvalidEscape("/a~2b".to_string()))
+        );
+        assert_eq!(
+            value.pointer("cars"),
+            Err(PointerError::InvalidPointer("cars".to_string()))
+        );
+    }
+
+    #[test]
+    pub fn test_pointer_mutation() {
+        let mut value: Value = JSON.parse().unwrap();
+        *value.pointer_mut("/cars/0/name").unwrap() = "\"Opel\"".parse().unwrap();
+        value.insert("/cars/-", "{}".parse().unwrap()).unwrap();
+        value.insert("/cars/0", "null".parse().unwrap()).unwrap();
+        let removed = value.remove("/a~1b/m~0n").unwrap();
+
+        assert_eq!(removed.to_string(), "8");
+        assert_eq!(
+            value.to_string(),
+            r#"{"a/b":{},"cars":[null,{"name":"Opel","plate":"20-13f"},{"name":"Fiat","plate":"30-14g"},{}]}"#
+        );
+        assert_eq!(
+            value.insert("/cars/9", "1".parse().unwrap()),
+            Err(PointerError::NotFound("/cars/9".to_string()))
+        );
+        assert_eq!(
+            value.remove("/missing"),
+            Err(PointerError::NotFound("/missing".to_string()))
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use crate::alloc::borrow::ToOwned;
+use crate::mapper;
+use crate::serializer;
+use crate::serializer::test::{check_roundtrip, decode_both, Strict, A};
+use crate::{Deserialize, JsonKey, Serialize};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum Status {
+    Active,
+    #[Rename = "on_hold"]
+    OnHold,
+    Closed,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[IntegerDiscriminant]
+pub enum Priority {
+    Low = 1,
+    High = 5,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Ticket {
+    pub status: Status,
+    pub priority: Priority,
+    pub history: Vec<Status>,
+}
+
+#[test]
+pub fn test_unit_enums() {
+    use serializer::{DecodeError, JsonType};
+
+    let ticket = Ticket {
+        status: Status::OnHold,
+        priority: Priority::High,
+        history: vec![Status::Active, Status::Closed],
+    };
+    let json = r#"{"history":["Active","Closed"],"priority":5,"status":"on_hold"}"#;
+    check_roundtrip(ticket, json);
+
+    let error = serializer::decode::<Status>(r#""OnHold""#.to_string()).unwrap_err();
+    assert!(matches!(
+        error,
+        DecodeError::InvalidValue { ref value, .. } if value == "OnHold"
+    ));
+    let error = serializer::decode::<Priority>("2".to_string()).unwrap_err();
+    assert!(matches!(
+        error,
+        DecodeError::InvalidValue { ref value, .. } if value == "2"
+    ));
+    let error = serializer::decode::<Status>("1".to_string()).unwrap_err();
+    assert_eq!(
+        error,
+        DecodeError::InvalidType {
+            path: "$".to_string(),
+            expected: JsonType::String,
+            found: JsonType::Number,
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum Shape {
+    Empty,
+    Circle(f64),
+    Line(i32, i32),
+    Rect {
+        width: u32,
+        #[Rename = "h"]
+        height: u32,
+    },
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[Tag = "type"]
+pub enum Event {
+    Ping,
+    Login { user: String, admin: Option<bool> },
+    Wrapped(A),
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[Tag = "type"]
+pub enum Holder<T> {
+    Held(T),
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[Tag = "kind"]
+pub enum Checked {
+    Exact(Strict),
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[Tag = "t"]
+#[Content = "c"]
+pub enum Message {
+    Quit,
+    Move(i32, i32),
+    Say(String),
+    Point { x: i32 },
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[Untagged]
+pub enum Loose {
+    Nothing,
+    Number(i64),
+    Pair(String, bool),
+    Named { name: String },
+}
+
+#[test]
+pub fn test_data_enums() {
+    use serializer::DecodeError;
+
+    check_roundtrip(Shape::Empty, r#""Empty""#);
+    check_roundtrip(Shape::Circle(1.5), r#"{"Circle":1.5}"#);
+    check_roundtrip(Shape::Line(1, -2), r#"{"Line":[1,-2]}"#);
+    check_roundtrip(
+        Shape::Rect {
+            width: 3,
+            height: 4,
+        },
+        r#"{"Rect":{"h":4,"width":3}}"#,
+    );
+
+    check_roundtrip(Event::Ping, r#"{"type":"Ping"}"#);
+    check_roundtrip(
+        Event::Login {
+            user: "ann".to_string(),
+            admin: None,
+        },
+        r#"{"admin":null,"type":"Login","user":"ann"}"#,
+    );
+    check_roundtrip(
+        Event::Wrapped(A {
+            a: 1,
+            b: "x".to_string(),
+        }),
+        r#"{"aJson":1,"b":"x","type":"Wrapped"}"#,
+    );
+
+    check_roundtrip(Message::Quit, r#"{"t":"Quit"}"#);
+    check_roundtrip(Message::Move(1, 2), r#"{"c":[1,2],"t":"Move"}"#);
+    check_roundtrip(Message::Say("hi".to_string()), r#"{"c":"hi","t":"Say"}"#);
+    check_roundtrip(Message::Point { x: 7 }, r#"{"c":{"x":7},"t":"Point"}"#);
+
+    check_roundtrip(Loose::Nothing, "null");
+    check_roundtrip(Loose::Number(3), "3");
+    check_roundtrip(Loose::Pair("a".to_string(), true), r#"["a",true]"#);
+    check_roundtrip(
+        Loose::Named {
+            name: "n".to_string(),
+        },
+        r#"{"name":"n"}"#,
+    );
+
+    let error = serializer::decode::<Shape>(r#"{"Rect": {"width": 1}}"#.to_string()).unwrap_err();
+    assert_eq!(
+        error,
+        DecodeError::MissingField {
+            path: "$.Rect.h".to_string(),
+            field: "h".to_string(),
+        }
+    );
+    let error = serializer::decode::<Shape>(r#"{"Circle": 1, "Empty": null}"#.to_string());
+    assert!(matches!(error, Err(DecodeError::InvalidLength { .. })));
+    assert_eq!(decode_both::<Shape>(r#"{"Empty": null}"#), Shape::Empty);
+    let error = serializer::decode::<Shape>(r#"{"Empty": 1}"#.to_string()).unwrap_err();
+    assert_eq!(error.path(), Some("$.Empty"));
+
+    // The tag goes among the content's members in key order
+    let mut counts = BTreeMap::new();
+    counts.insert("a".to_string(), 1u8);
+    counts.insert("z".to_string(), 2);
+    check_roundtrip(Holder::Held(counts), r#"{"a":1,"type":"Held","z":2}"#);
+    // Content breaking its SerializeObject promise has nowhere to take the tag
+    struct Number;
+    impl serializer::Serialize for Number {
+        fn serialize(&self) -> crate::mapper::Value {
+            serializer::Serialize::serialize(&5u8)
+        }
+    }
+    impl serializer::SerializeObject for Number {}
+    assert!(serializer::encode_with(Holder::Held(Number), &Default::default()).is_err());
+    let mut output = String::new();
+    assert!(serializer::encode_to(Holder::Held(Number), &mut output).is_err());
+    let error = serializer::decode::<Event>(r#"{"user": "ann"}"#.to_string()).unwrap_err();
+    assert_eq!(error.path(), Some("$.type"));
+    assert!(matches!(error, DecodeError::MissingField { .. }));
+    let error = serializer::decode::<Event>(r#"{"type": "Logout"}"#.to_string()).unwrap_err();
+    assert!(matches!(
+        error,
+        DecodeError::InvalidValue { ref path, ref value, .. } if path == "$.type" && value == "Logout"
+    ));
+    let error = serializer::decode::<Message>(r#"{"t": "Say"}"#.to_string()).unwrap_err();
+    assert_eq!(error.path(), Some("$.c"));
+    let error =
+        serializer::decode::<Message>(r#"{"t": "Move", "c": [1]}"#.to_string()).unwrap_err();
+    assert_eq!(error.path(), Some("$.c"));
+    assert!(matches!(error, DecodeError::InvalidLength { .. }));
+    let error = serializer::decode::<Loose>("[1]".to_string()).unwrap_err();
+    assert!(matches!(error, DecodeError::InvalidValue { ref value, .. } if value == "[1]"));
+
+    // The tag is not among the content's members, even for content denying unknown ones
+    let exact = Checked::Exact(Strict { a: 1, pair: (2, 3) });
+    let json = r#"{"a": 1, "kind": "Exact", "pair": [2, 3]}"#;
+    assert_eq!(decode_both::<Checked>(json), exact);
+    let json = r#"{"a": 1, "kind": "Exact", "pair": [2, 3], "x": 0}"#;
+    let error = serializer::decode::<Checked>(json.to_string()).unwrap_err();
+    assert!(matches!(error, DecodeError::UnknownField { .. }));
+}
+
+#[test]
+pub fn test_decode_all_enums() {
+    fn paths<T: serializer::Deserialize + core::fmt::Debug>(json: &str) -> Vec<String> {
+        let errors = serializer::decode_all::<T>(json.to_string()).unwrap_err();
+        errors
+            .iter()
+            .map(|error| error.path().unwrap().to_string())
+            .collect()
+    }
+
+    assert_eq!(
+        paths::<Shape>(r#"{"Rect": {"width": "1"}}"#),
+        ["$.Rect.width", "$.Rect.h"]
+    );
+    assert_eq!(
+        paths::<Shape>(r#"{"Line": ["1", "2"]}"#),
+        ["$.Line[0]", "$.Line[1]"]
+    );
+    assert_eq!(paths::<Shape>(r#"{"Square": 1}"#), ["$"]);
+    assert_eq!(
+        paths::<Event>(r#"{"type": "Login", "user": 1, "admin": 2}"#),
+        ["$.user", "$.admin"]
+    );
+    assert_eq!(
+        paths::<Checked>(r#"{"kind": "Exact", "pair": [2], "x": 0}"#),
+        ["$.x", "$.a", "$.pair"]
+    );
+    assert_eq!(paths::<Event>(r#"{"user": "ann"}"#), ["$.type"]);
+    assert_eq!(
+        paths::<Message>(r#"{"t": "Move", "c": ["1", "2"]}"#),
+        ["$.c[0]", "$.c[1]"]
+    );
+    assert_eq!(paths::<Message>(r#"{"t": "Say"}"#), ["$.c"]);
+    assert_eq!(paths::<Loose>(r#"{"name": 1}"#), ["$"]);
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Deserialize, Serialize, JsonKey)]
+pub struct UserId(u64);
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Nickname(Option<String>);
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Point(i32, i32, String);
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Marker;
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Profile {
+    pub id: UserId,
+    pub nickname: Nickname,
+    pub origin: Point,
+    pub marker: Marker,
+}
+
+#[test]
+pub fn test_tuple_structs() {
+    use serializer::DecodeError;
+
+    check_roundtrip(UserId(42), "42");
+    check_roundtrip(Point(1, -2, "p".to_string()), r#"[1,-2,"p"]"#);
+    check_roundtrip(Marker, "null");
+
+    let profile = Profile {
+        id: UserId(7),
+        nickname: Nickname(None),
+        origin: Point(0, 1, "o".to_string()),
+        marker: Marker,
+    };
+    let json = r#"{"id":7,"marker":null,"nickname":null,"origin":[0,1,"o"]}"#;
+    check_roundtrip(profile, json);
+
+    let decoded: Profile =
+        serializer::decode(r#"{"id": 7, "origin": [0, 1, "o"]}"#.to_string()).unwrap();
+    assert_eq!(decoded.nickname, Nickname(None));
+    assert_eq!(decoded.marker, Marker);
+
+    let ids: BTreeMap<String, UserId> = serializer::decode(r#"{"a": 1}"#.to_string()).unwrap();
+    assert_eq!(ids["a"], UserId(1));
+
+    let mut names = BTreeMap::new();
+    names.insert(UserId(10), "ann".to_string());
+    names.insert(UserId(2), "bob".to_string());
+    check_roundtrip(names, r#"{"10":"ann","2":"bob"}"#);
+    let error =
+        serializer::decode::<BTreeMap<UserId, String>>(r#"{"x": "ann"}"#.to_string()).unwrap_err();
+    assert!(matches!(error, DecodeError::InvalidValue { ref value, .. } if value == "x"));
+
+    let error = serializer::decode::<Profile>(r#"{"id": 7, "origin": [0, "1", "o"]}"#.to_string())
+        .unwrap_err();
+    assert_eq!(error.path(), Some("$.origin[1]"));
+    let error = serializer::decode::<Point>("[0, 1]".to_string()).unwrap_err();
+    assert!(matches!(
+        error,
+        DecodeError::InvalidLength {
+            expected: 3,
+            found: 2,
+            ..
+        }
+    ));
+    assert!(serializer::decode::<Marker>("0".to_string()).is_err());
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u32,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Wrapper<T>(T);
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Titled<'a> {
+    pub title: Cow<'a, str>,
+}
+
+#[derive(Serialize)]
+pub struct Borrowed<'a, T: ?Sized> {
+    pub name: &'a T,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[SerializeBound = "K: serializer::JsonKey, V: serializer::Serialize"]
+#[DeserializeBound = "K: serializer::JsonKey + Ord, V: serializer::Deserialize"]
+pub struct Keyed<K, V>
+where
+    K: Ord,
+{
+    pub entries: BTreeMap<K, V>,
+}
+
+#[test]
+pub fn test_generics() {
+    let page = Page {
+        items: vec![A {
+            a: 1,
+            b: "x".to_string(),
+        }],
+        total: 1,
+    };
+    check_roundtrip(page, r#"{"items":[{"aJson":1,"b":"x"}],"total":1}"#);
+    check_roundtrip(
+        Page {
+            items: vec![Wrapper(1u8), Wrapper(2)],
+            total: 2,
+        },
+        r#"{"items":[1,2],"total":2}"#,
+    );
+    check_roundtrip(Either::<u8, String>::Left(3), r#"{"Left":3}"#);
+    check_roundtrip(
+        Either::<u8, String>::Right("r".to_string()),
+        r#"{"Right":"r"}"#,
+    );
+    check_roundtrip(
+        Titled {
+            title: Cow::Borrowed("t"),
+        },
+        r#"{"title":"t"}"#,
+    );
+    assert_eq!(
+        serializer::encode(Borrowed { name: "n" }),
+        r#"{"name":"n"}"#
+    );
+
+    let mut entries = BTreeMap::new();
+    entries.insert(3u16, vec![true]);
+    check_roundtrip(Keyed { entries }, r#"{"entries":{"3":[true]}}"#);
+}
@@ -1,121 +1,453 @@
-use crate::mapper::Value;
-use crate::serializer::{DecodeError, Deserialize};
+use crate::lexer::{Token, TokenType};
+use crate::mapper::{scalar_type, JsonType, Mapper, Value};
+use crate::serializer::{Coercion, DecodeError, Deserialize, JsonKey};
+use alloc::borrow::{Cow, ToOwned};
 use alloc::boxed::Box;
-use alloc::format;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::rc::Rc;
 use alloc::string::String;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
 use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
 
-pub fn parse_token<T>(value: Option<&Value>) -> Result<T, DecodeError>
+/// Reads the token of a scalar, checking that it is the JSON type `T` is decoded from.
+pub fn parse_token<T>(value: Option<&Value>, expected: JsonType) -> Result<T, DecodeError>
 where
     T: FromStr,
 {
-    let value = match value {
-        None => {
-            return Err(DecodeError::ParseError(format!(
-                "Could not parse {} to {}",
-                "None",
-                core::any::type_name::<T>()
-            )))
+    parse_token_with(value, expected, Coercion::STRICT)
+}
+
+/// Like [`parse_token`], also accepting the JSON types `coercion` converts from.
+pub fn parse_token_with<T>(
+    value: Option<&Value>,
+    expected: JsonType,
+    coercion: Coercion,
+) -> Result<T, DecodeError>
+where
+    T: FromStr,
+{
+    parse_scalar(scalar_token(value, expected)?, expected, coercion)
+}
+
+// The token of a scalar value, failing for a missing value or one of another `expected` type
+fn scalar_token(value: Option<&Value>, expected: JsonType) -> Result<&Token, DecodeError> {
+    match value {
+        None => Err(DecodeError::missing_value()),
+        Some(Value::Token(token)) => Ok(token),
+        Some(v) => Err(DecodeError::invalid_type(expected, v.json_type())),
+    }
+}
+
+/// Like [`parse_token_with`], for a token read straight from the stream.
+pub fn parse_scalar<T>(
+    token: &Token,
+    expected: JsonType,
+    coercion: Coercion,
+) -> Result<T, DecodeError>
+where
+    T: FromStr,
+{
+    match (expected, scalar_type(token)) {
+        (expected, found) if expected == found => token.to::<T>(),
+        (JsonType::Number, JsonType::String) if coercion.numbers_from_strings => token.to::<T>(),
+        (JsonType::Bool, JsonType::String) if coercion.bools_from_strings => token.to::<T>(),
+        (JsonType::Bool, JsonType::Number) if coercion.bools_from_numbers => {
+            let literal = match token.literal.as_str() {
+                "1" => "true",
+                "0" => "false",
+                other => other,
+            };
+            T::from_str(literal).map_err(|_| DecodeError::invalid_value::<T>(&token.literal))
         }
-        Some(v) => v,
-    };
+        (expected, found) => Err(DecodeError::invalid_type(expected, found)),
+    }
+}
 
-    let token = match value {
-        Value::Token(t) => t,
-        _ => return Err(DecodeError::UnexpectedType),
+/// Borrows the contents of a JSON string, such as the name of an enum variant.
+pub fn parse_str(value: Option<&Value>) -> Result<&str, DecodeError> {
+    match value {
+        None => Err(DecodeError::missing_value()),
+        Some(Value::Token(token)) if matches!(token.token_type, TokenType::String(_)) => {
+            Ok(&token.literal)
+        }
+        Some(v) => Err(DecodeError::invalid_type(JsonType::String, v.json_type())),
+    }
+}
+
+/// Reads the member `tag` naming the variant of an internally or adjacently tagged
+/// enum.
+pub fn parse_tag<'a>(value: Option<&'a Value>, tag: &str) -> Result<&'a str, DecodeError> {
+    let object = match value {
+        None => return Err(DecodeError::missing_value()),
+        Some(Value::Object(object)) => object,
+        Some(v) => return Err(DecodeError::invalid_type(JsonType::Object, v.json_type())),
     };
+    match object.get(tag) {
+        None => Err(DecodeError::missing_field(tag)),
+        member => parse_str(member).map_err(|e| e.at_field(tag)),
+    }
+}
+
+/// Floats also accept the strings written by [`crate::formatter::NonFinite::String`]
+/// when `coercion` allows it.
+fn parse_float<T>(token: &Token, coercion: Coercion) -> Result<T, DecodeError>
+where
+    T: FromStr,
+{
+    match token.token_type {
+        TokenType::String(_)
+            if coercion.non_finite_from_strings
+                && matches!(token.literal.as_str(), "NaN" | "Infinity" | "-Infinity") =>
+        {
+            token.to::<T>()
+        }
+        _ => parse_scalar(token, JsonType::Number, coercion),
+    }
+}
+
+/// 128-bit integers also accept the strings written with
+/// [`crate::formatter::EncodeOptions::int128_as_string`] when `coercion` allows it.
+fn parse_int128<T>(token: &Token, coercion: Coercion) -> Result<T, DecodeError>
+where
+    T: FromStr,
+{
+    let coercion = coercion.union(Coercion {
+        numbers_from_strings: coercion.int128_from_strings,
+        ..Coercion::STRICT
+    });
+    parse_scalar(token, JsonType::Number, coercion)
+}
 
-    return token.to::<T>();
+/// Reads a scalar straight from the stream with `parse`, without building a
+/// [`Value`]. Other values go to `deserialize_with`, which reports them.
+fn read_scalar<T, F>(mapper: &mut Mapper, parse: F) -> Result<T, DecodeError>
+where
+    T: Deserialize,
+    F: FnOnce(&Token, Coercion) -> Result<T, DecodeError>,
+{
+    let coercion = mapper.coercion;
+    if let Some(token) = mapper.next_scalar() {
+        return parse(token, coercion);
+    }
+    let value = mapper.parse_element()?;
+    T::deserialize_with(Some(&value), coercion)
 }
 
 impl Deserialize for u8 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
     }
 }
 
 impl Deserialize for u16 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
     }
 }
 
 impl Deserialize for u32 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
     }
 }
 
 impl Deserialize for u64 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
     }
 }
 
 impl Deserialize for usize {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
     }
 }
 
 impl Deserialize for i8 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
     }
 }
 
 impl Deserialize for i16 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
     }
 }
 
 impl Deserialize for i32 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
     }
 }
 
 impl Deserialize for i64 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
     }
 }
 
 impl Deserialize for isize {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Number, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Number, coercion)
+        })
+    }
+}
+
+impl Deserialize for u128 {
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_int128(scalar_token(value, JsonType::Number)?, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, parse_int128)
+    }
+}
+
+impl Deserialize for i128 {
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_int128(scalar_token(value, JsonType::Number)?, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, parse_int128)
     }
 }
 
+macro_rules! deserialize_non_zero {
+    ($($non_zero:ident($int:ty)),*) => {$(
+        impl Deserialize for $non_zero {
+            fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+                Self::deserialize_with(value, Coercion::STRICT)
+            }
+
+            fn deserialize_with(
+                value: Option<&Value>,
+                coercion: Coercion,
+            ) -> Result<Self, DecodeError> {
+                let int = <$int>::deserialize_with(value, coercion)?;
+                $non_zero::new(int).ok_or_else(|| DecodeError::invalid_value::<$non_zero>("0"))
+            }
+
+            fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+                let int = <$int>::deserialize_from(mapper)?;
+                $non_zero::new(int).ok_or_else(|| DecodeError::invalid_value::<$non_zero>("0"))
+            }
+        }
+    )*};
+}
+
+deserialize_non_zero!(
+    NonZeroU8(u8),
+    NonZeroU16(u16),
+    NonZeroU32(u32),
+    NonZeroU64(u64),
+    NonZeroU128(u128),
+    NonZeroUsize(usize),
+    NonZeroI8(i8),
+    NonZeroI16(i16),
+    NonZeroI32(i32),
+    NonZeroI64(i64),
+    NonZeroI128(i128),
+    NonZeroIsize(isize)
+);
+
 impl Deserialize for f32 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_float(scalar_token(value, JsonType::Number)?, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, parse_float)
     }
 }
 
 impl Deserialize for f64 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_float(scalar_token(value, JsonType::Number)?, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, parse_float)
     }
 }
 
 impl Deserialize for bool {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        parse_token_with(value, JsonType::Bool, coercion)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, coercion| {
+            parse_scalar(token, JsonType::Bool, coercion)
+        })
     }
 }
 
 impl Deserialize for char {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        parse_token(value, JsonType::String)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, _| {
+            parse_scalar(token, JsonType::String, Coercion::STRICT)
+        })
     }
 }
 
 impl Deserialize for String {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        parse_token(value)
+        parse_token(value, JsonType::String)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        read_scalar(mapper, |token, _| {
+            parse_scalar(token, JsonType::String, Coercion::STRICT)
+        })
+    }
+}
+
+impl Deserialize for Value {
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        match value {
+            None => Err(DecodeError::missing_value()),
+            Some(v) => Ok(v.clone()),
+        }
+    }
+
+    fn deserialize_null() -> Option<Self> {
+        Some(Value::null())
     }
 }
 
@@ -124,14 +456,44 @@ where
     T: Deserialize,
 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
         match value {
             None => Ok(None),
+            Some(v) if v.is_null() => Ok(T::deserialize_null()),
             Some(v) => {
-                let res = T::deserialize(Some(v))?;
+                let res = T::deserialize_with(Some(v), coercion)?;
                 Ok(Some(res))
             }
         }
     }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        match value {
+            Some(v) if !v.is_null() => T::deserialize_all(Some(v), coercion, errors).map(Some),
+            _ => Self::deserialize_with(value, coercion)
+                .map_err(|error| errors.push(error))
+                .ok(),
+        }
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        if mapper.next_null() {
+            return Ok(T::deserialize_null());
+        }
+        let res = T::deserialize_from(mapper)?;
+        Ok(Some(res))
+    }
+
+    fn deserialize_null() -> Option<Self> {
+        Some(T::deserialize_null())
+    }
 }
 
 impl<T> Deserialize for Box<T>
@@ -139,9 +501,257 @@ where
     T: Deserialize,
 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
-        let res = T::deserialize(value)?;
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        let res = T::deserialize_with(value, coercion)?;
+        Ok(Box::new(res))
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_all(value, coercion, errors).map(Box::new)
+    }
+
+    fn deserialize_members(
+        value: &Value,
+        tag: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_members(value, tag, coercion, errors).map(Box::new)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        let res = T::deserialize_from(mapper)?;
         Ok(Box::new(res))
     }
+
+    fn deserialize_null() -> Option<Self> {
+        T::deserialize_null().map(Box::new)
+    }
+}
+
+impl<T> Deserialize for Rc<T>
+where
+    T: Deserialize,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        let res = T::deserialize_with(value, coercion)?;
+        Ok(Rc::new(res))
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_all(value, coercion, errors).map(Rc::new)
+    }
+
+    fn deserialize_members(
+        value: &Value,
+        tag: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_members(value, tag, coercion, errors).map(Rc::new)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        let res = T::deserialize_from(mapper)?;
+        Ok(Rc::new(res))
+    }
+
+    fn deserialize_null() -> Option<Self> {
+        T::deserialize_null().map(Rc::new)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> Deserialize for Arc<T>
+where
+    T: Deserialize,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        let res = T::deserialize_with(value, coercion)?;
+        Ok(Arc::new(res))
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_all(value, coercion, errors).map(Arc::new)
+    }
+
+    fn deserialize_members(
+        value: &Value,
+        tag: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_members(value, tag, coercion, errors).map(Arc::new)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        let res = T::deserialize_from(mapper)?;
+        Ok(Arc::new(res))
+    }
+
+    fn deserialize_null() -> Option<Self> {
+        T::deserialize_null().map(Arc::new)
+    }
+}
+
+/// Always produces [`Cow::Owned`], since decoding copies out of the input.
+impl<T> Deserialize for Cow<'_, T>
+where
+    T: ToOwned + ?Sized,
+    T::Owned: Deserialize,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        let res = T::Owned::deserialize_with(value, coercion)?;
+        Ok(Cow::Owned(res))
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::Owned::deserialize_all(value, coercion, errors).map(Cow::Owned)
+    }
+
+    fn deserialize_members(
+        value: &Value,
+        tag: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::Owned::deserialize_members(value, tag, coercion, errors).map(Cow::Owned)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        let res = T::Owned::deserialize_from(mapper)?;
+        Ok(Cow::Owned(res))
+    }
+
+    fn deserialize_null() -> Option<Self> {
+        T::Owned::deserialize_null().map(Cow::Owned)
+    }
+}
+
+impl<T> Deserialize for Cell<T>
+where
+    T: Deserialize,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        let res = T::deserialize_with(value, coercion)?;
+        Ok(Cell::new(res))
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_all(value, coercion, errors).map(Cell::new)
+    }
+
+    fn deserialize_members(
+        value: &Value,
+        tag: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_members(value, tag, coercion, errors).map(Cell::new)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        let res = T::deserialize_from(mapper)?;
+        Ok(Cell::new(res))
+    }
+
+    fn deserialize_null() -> Option<Self> {
+        T::deserialize_null().map(Cell::new)
+    }
+}
+
+impl<T> Deserialize for RefCell<T>
+where
+    T: Deserialize,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        let res = T::deserialize_with(value, coercion)?;
+        Ok(RefCell::new(res))
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_all(value, coercion, errors).map(RefCell::new)
+    }
+
+    fn deserialize_members(
+        value: &Value,
+        tag: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        T::deserialize_members(value, tag, coercion, errors).map(RefCell::new)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        let res = T::deserialize_from(mapper)?;
+        Ok(RefCell::new(res))
+    }
+
+    fn deserialize_null() -> Option<Self> {
+        T::deserialize_null().map(RefCell::new)
+    }
+}
+
+impl Deserialize for () {
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        match value {
+            Some(v) if !v.is_null() => {
+                Err(DecodeError::invalid_type(JsonType::Null, v.json_type()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn deserialize_null() -> Option<Self> {
+        Some(())
+    }
 }
 
 impl<T> Deserialize for Vec<T>
@@ -149,6 +759,10 @@ where
     T: Deserialize,
 {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
         let value = match value {
             None => return Ok(Vec::new()),
             Some(v) => v,
@@ -156,13 +770,735 @@ where
         match value {
             Value::Array(array) => {
                 let mut vec = Vec::new();
-                for item in array {
-                    let res = T::deserialize(Some(item))?;
+                for (index, item) in array.iter().enumerate() {
+                    let res =
+                        T::deserialize_with(Some(item), coercion).map_err(|e| e.at_index(index))?;
                     vec.push(res);
                 }
                 Ok(vec)
             }
-            _ => Err(DecodeError::UnexpectedType),
+            _ => Err(DecodeError::invalid_type(
+                JsonType::Array,
+                value.json_type(),
+            )),
+        }
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        let array = match value {
+            Some(Value::Array(array)) => array,
+            _ => {
+                return Self::deserialize_with(value, coercion)
+                    .map_err(|error| errors.push(error))
+                    .ok()
+            }
+        };
+        let mut vec = Vec::new();
+        let mut failed = false;
+        for (index, item) in array.iter().enumerate() {
+            let mut found = Vec::new();
+            match T::deserialize_all(Some(item), coercion, &mut found) {
+                Some(res) => vec.push(res),
+                None => failed = true,
+            }
+            errors.extend(found.into_iter().map(|error| error.at_index(index)));
+        }
+        (!failed).then_some(vec)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        let mut vec = Vec::new();
+        mapper.expect_type(JsonType::Array)?;
+        mapper.begin_array()?;
+        while mapper.next_element()? {
+            let res = T::deserialize_from(mapper).map_err(|e| e.at_index(vec.len()))?;
+            vec.push(res);
         }
+        Ok(vec)
+    }
+}
+
+fn deserialize_map<K, V, M>(value: Option<&Value>, coercion: Coercion) -> Result<M, DecodeError>
+where
+    K: JsonKey,
+    V: Deserialize,
+    M: Default + Extend<(K, V)>,
+{
+    let mut map = M::default();
+    match value {
+        None => {}
+        Some(Value::Object(object)) => {
+            for (key, item) in object {
+                let res = V::deserialize_with(Some(item), coercion).map_err(|e| e.at_field(key))?;
+                map.extend([(K::from_key(key).map_err(|e| e.at_field(key))?, res)]);
+            }
+        }
+        Some(v) => return Err(DecodeError::invalid_type(JsonType::Object, v.json_type())),
+    }
+    Ok(map)
+}
+
+// `skipped` names the tag of the internally tagged enum holding the map, if any
+fn deserialize_map_all<K, V, M>(
+    value: Option<&Value>,
+    coercion: Coercion,
+    skipped: Option<&str>,
+    errors: &mut Vec<DecodeError>,
+) -> Option<M>
+where
+    K: JsonKey,
+    V: Deserialize,
+    M: Default + Extend<(K, V)>,
+{
+    let object = match value {
+        Some(Value::Object(object)) => object,
+        _ => {
+            return deserialize_map(value, coercion)
+                .map_err(|error| errors.push(error))
+                .ok()
+        }
+    };
+    let mut map = M::default();
+    let mut failed = false;
+    for (key, item) in object
+        .iter()
+        .filter(|(key, _)| Some(key.as_str()) != skipped)
+    {
+        let mut found = Vec::new();
+        let res = V::deserialize_all(Some(item), coercion, &mut found);
+        match K::from_key(key) {
+            Ok(key) => map.extend(res.map(|res| (key, res))),
+            Err(error) => found.push(error),
+        }
+        failed |= !found.is_empty();
+        errors.extend(found.into_iter().map(|error| error.at_field(key)));
+    }
+    (!failed).then_some(map)
+}
+
+fn deserialize_map_from<K, V, M>(mapper: &mut Mapper) -> Result<M, DecodeError>
+where
+    K: JsonKey,
+    V: Deserialize,
+    M: Default + Extend<(K, V)>,
+{
+    let mut map = M::default();
+    mapper.expect_type(JsonType::Object)?;
+    mapper.begin_object()?;
+    while let Some(key) = mapper.next_key()? {
+        let res = V::deserialize_from(mapper).map_err(|e| e.at_field(&key))?;
+        map.extend([(K::from_key(&key).map_err(|e| e.at_field(&key))?, res)]);
+    }
+    Ok(map)
+}
+
+impl<T> Deserialize for VecDeque<T>
+where
+    T: Deserialize,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_with(value, coercion)?.into())
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        Vec::deserialize_all(value, coercion, errors).map(Into::into)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_from(mapper)?.into())
+    }
+}
+
+impl<T> Deserialize for LinkedList<T>
+where
+    T: Deserialize,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_with(value, coercion)?
+            .into_iter()
+            .collect())
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        Vec::deserialize_all(value, coercion, errors).map(|vec| vec.into_iter().collect())
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_from(mapper)?.into_iter().collect())
+    }
+}
+
+impl<T> Deserialize for BinaryHeap<T>
+where
+    T: Deserialize + Ord,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_with(value, coercion)?.into())
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        Vec::deserialize_all(value, coercion, errors).map(Into::into)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_from(mapper)?.into())
+    }
+}
+
+impl<T> Deserialize for BTreeSet<T>
+where
+    T: Deserialize + Ord,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_with(value, coercion)?
+            .into_iter()
+            .collect())
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        Vec::deserialize_all(value, coercion, errors).map(|vec| vec.into_iter().collect())
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_from(mapper)?.into_iter().collect())
+    }
+}
+
+impl<K, V> Deserialize for BTreeMap<K, V>
+where
+    K: JsonKey + Ord,
+    V: Deserialize,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        deserialize_map(value, coercion)
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        deserialize_map_all(value, coercion, None, errors)
+    }
+
+    fn deserialize_members(
+        value: &Value,
+        tag: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        deserialize_map_all(Some(value), coercion, Some(tag), errors)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        deserialize_map_from(mapper)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, H> Deserialize for HashSet<T, H>
+where
+    T: Deserialize + Eq + Hash,
+    H: BuildHasher + Default,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_with(value, coercion)?
+            .into_iter()
+            .collect())
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        Vec::deserialize_all(value, coercion, errors).map(|vec| vec.into_iter().collect())
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        Ok(Vec::deserialize_from(mapper)?.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, H> Deserialize for HashMap<K, V, H>
+where
+    K: JsonKey + Eq + Hash,
+    V: Deserialize,
+    H: BuildHasher + Default,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        deserialize_map(value, coercion)
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        deserialize_map_all(value, coercion, None, errors)
+    }
+
+    fn deserialize_members(
+        value: &Value,
+        tag: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        deserialize_map_all(Some(value), coercion, Some(tag), errors)
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        deserialize_map_from(mapper)
+    }
+}
+
+impl<T, const N: usize> Deserialize for [T; N]
+where
+    T: Deserialize,
+{
+    fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+        Self::deserialize_with(value, Coercion::STRICT)
+    }
+
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        let vec = Vec::<T>::deserialize_with(value, coercion)?;
+        let found = vec.len();
+        vec.try_into()
+            .map_err(|_| DecodeError::invalid_length(N, found))
+    }
+
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        let vec = Vec::<T>::deserialize_all(value, coercion, errors)?;
+        let found = vec.len();
+        vec.try_into()
+            .map_err(|_| errors.push(DecodeError::invalid_length(N, found)))
+            .ok()
+    }
+
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        let vec = Vec::<T>::deserialize_from(mapper)?;
+        let found = vec.len();
+        vec.try_into()
+            .map_err(|_| DecodeError::invalid_length(N, found))
+    }
+}
+
+/// Reads the elements left in an array whose first `read` elements have been
+/// consumed, failing with their total count if there are any.
+fn end_tuple(mapper: &mut Mapper, read: usize) -> Result<(), DecodeError> {
+    let mut found = read;
+    while mapper.next_element()? {
+        mapper.skip_element()?;
+        found += 1;
+    }
+    if found != read {
+        return Err(DecodeError::invalid_length(read, found));
+    }
+    Ok(())
+}
+
+macro_rules! deserialize_tuple {
+    ($($len:literal => ($($index:tt $name:ident)+))+) => {$(
+        impl<$($name),+> Deserialize for ($($name,)+)
+        where
+            $($name: Deserialize,)+
+        {
+            fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError> {
+                Self::deserialize_with(value, Coercion::STRICT)
+            }
+
+            fn deserialize_with(
+                value: Option<&Value>,
+                coercion: Coercion,
+            ) -> Result<Self, DecodeError> {
+                match value {
+                    Some(Value::Array(array)) if array.len() == $len => {
+                        Ok(($(
+                            $name::deserialize_with(Some(&array[$index]), coercion)
+                                .map_err(|e| e.at_index($index))?,
+                        )+))
+                    }
+                    Some(Value::Array(array)) => {
+                        Err(DecodeError::invalid_length($len, array.len()))
+                    }
+                    None => Err(DecodeError::invalid_length($len, 0)),
+                    Some(v) => Err(DecodeError::invalid_type(JsonType::Array, v.json_type())),
+                }
+            }
+
+            fn deserialize_all(
+                value: Option<&Value>,
+                coercion: Coercion,
+                errors: &mut Vec<DecodeError>,
+            ) -> Option<Self> {
+                let array = match value {
+                    Some(Value::Array(array)) if array.len() == $len => array,
+                    _ => {
+                        return Self::deserialize_with(value, coercion)
+                            .map_err(|error| errors.push(error))
+                            .ok()
+                    }
+                };
+                let tuple = ($({
+                    let mut found = Vec::new();
+                    let res = $name::deserialize_all(Some(&array[$index]), coercion, &mut found);
+                    errors.extend(found.into_iter().map(|error| error.at_index($index)));
+                    res
+                },)+);
+                Some(($(tuple.$index?,)+))
+            }
+
+            fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+                mapper.expect_type(JsonType::Array)?;
+                mapper.begin_array()?;
+                let tuple = ($(
+                    match mapper.next_element()? {
+                        true => $name::deserialize_from(mapper).map_err(|e| e.at_index($index))?,
+                        false => return Err(DecodeError::invalid_length($len, $index)),
+                    },
+                )+);
+                end_tuple(mapper, $len)?;
+                Ok(tuple)
+            }
+        }
+    )+};
+}
+
+deserialize_tuple! {
+    1 => (0 T0)
+    2 => (0 T0 1 T1)
+    3 => (0 T0 1 T1 2 T2)
+    4 => (0 T0 1 T1 2 T2 3 T3)
+    5 => (0 T0 1 T1 2 T2 3 T3 4 T4)
+    6 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5)
+    7 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6)
+    8 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7)
+    9 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8)
+    10 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9)
+    11 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10)
+    12 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11)
+    13 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12)
+    14 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13)
+    15 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14)
+    16 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15)
+}
+
+#[cfg(test)]
+pub mod test {
+    use crate::alloc::borrow::ToOwned;
+    use crate::mapper;
+    use crate::serializer;
+    use crate::serializer::test::{check_roundtrip, decode_both, Node, A, C};
+    use crate::{Deserialize, Serialize};
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Wide {
+        pub id: u128,
+        pub offset: i128,
+        pub index: isize,
+        pub port: core::num::NonZeroU16,
+    }
+
+    #[test]
+    pub fn test_wide_integers() {
+        let wide = || Wide {
+            id: u128::MAX,
+            offset: i128::MIN,
+            index: -2,
+            port: core::num::NonZeroU16::new(8080).unwrap(),
+        };
+        let json = serializer::encode(wide());
+        assert_eq!(
+            json,
+            r#"{"id":340282366920938463463374607431768211455,"index":-2,"offset":-170141183460469231731687303715884105728,"port":8080}"#
+        );
+        assert_eq!(
+            serializer::Serialize::serialize(&wide())
+                .encode_json()
+                .len(),
+            json.len()
+        );
+        assert_eq!(serializer::decode::<Wide>(json).unwrap(), wide());
+
+        let options = serializer::EncodeOptions {
+            int128_as_string: true,
+            ..Default::default()
+        };
+        let json = serializer::encode_with(wide(), &options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"id":"340282366920938463463374607431768211455","index":-2,"offset":"-170141183460469231731687303715884105728","port":8080}"#
+        );
+        // Reading them back as strings takes opting in
+        assert!(serializer::decode::<Wide>(json.clone()).is_err());
+        let quoted = serializer::DecodeOptions {
+            coercion: serializer::Coercion {
+                int128_from_strings: true,
+                ..serializer::Coercion::STRICT
+            },
+        };
+        assert_eq!(
+            serializer::decode_with::<Wide>(json.clone(), &quoted).unwrap(),
+            wide()
+        );
+        let error = serializer::decode::<u128>(r#""42""#.to_string()).unwrap_err();
+        assert!(matches!(error, serializer::DecodeError::InvalidType { .. }));
+        let value: mapper::Value = r#""-42""#.parse().unwrap();
+        assert!(<i128 as serializer::Deserialize>::deserialize(Some(&value)).is_err());
+        assert_eq!(
+            <i128 as serializer::Deserialize>::deserialize_with(Some(&value), quoted.coercion),
+            Ok(-42)
+        );
+        // The opt-in covers only the 128-bit integers
+        assert!(serializer::decode_with::<i64>(r#""-42""#.to_string(), &quoted).is_err());
+
+        // The tree writes the same, and integers that fit in 64 bits stay numbers
+        let tree = serializer::Serialize::serialize(&wide());
+        let mut output = String::new();
+        crate::formatter::write_compact(&tree, &mut output, &options).unwrap();
+        assert_eq!(output, json);
+        let config = serializer::PrettyConfig {
+            options,
+            inline_array_width: Some(80),
+            ..Default::default()
+        };
+        assert_eq!(
+            serializer::encode_pretty(vec![u128::MAX, 5], &config).unwrap(),
+            r#"["340282366920938463463374607431768211455", 5]"#
+        );
+        assert_eq!(serializer::encode_with(-5i128, &options).unwrap(), "-5");
+
+        assert_eq!(
+            serializer::encode(10_000_000_000_000_000_000u128),
+            "10000000000000000000"
+        );
+        assert_eq!(
+            serializer::encode(u64::MAX as u128 + 1),
+            "18446744073709551616"
+        );
+        assert!(serializer::decode::<core::num::NonZeroI8>("0".to_string()).is_err());
+        assert!(serializer::decode::<core::num::NonZeroU8>("256".to_string()).is_err());
+    }
+
+    #[test]
+    pub fn test_pointers_and_references() {
+        let json = r#"{"label": "a", "weight": 1, "next": {"label": "b", "weight": 2}}"#;
+        let node: Node = serializer::decode(json.to_string()).unwrap();
+        assert_eq!(*node.next.as_ref().unwrap().label, "b");
+        assert_eq!(
+            serializer::encode(&node),
+            r#"{"label":"a","next":{"label":"b","next":null,"weight":2},"weight":1}"#
+        );
+
+        let names = ["x", "y"];
+        assert_eq!(serializer::encode(&names[..]), r#"["x","y"]"#);
+        assert_eq!(serializer::encode("quote\""), r#""quote\"""#);
+        let shared = alloc::rc::Rc::new(core::cell::RefCell::new(vec![1u8]));
+        shared.borrow_mut().push(2);
+        assert_eq!(serializer::encode(&shared), "[1,2]");
+        assert_eq!(serializer::encode(alloc::sync::Arc::new(-1i8)), "-1");
+        assert_eq!(
+            serializer::encode(alloc::borrow::Cow::Borrowed("z")),
+            r#""z""#
+        );
+        assert_eq!(serializer::encode(()), "null");
+
+        let cow: alloc::borrow::Cow<'_, str> =
+            serializer::decode(r#""owned""#.to_string()).unwrap();
+        assert_eq!(cow, "owned");
+        serializer::decode::<()>("null".to_string()).unwrap();
+        assert!(serializer::decode::<()>("1".to_string()).is_err());
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Request {
+        pub position: [f64; 2],
+        pub status: (String, u16),
+    }
+
+    #[test]
+    pub fn test_arrays_and_tuples() {
+        let json = r#"{"position": [51.5, -0.12], "status": ["GET", 200]}"#;
+        let request: Request = decode_both(json);
+        assert_eq!(request.position, [51.5, -0.12]);
+        assert_eq!(request.status, ("GET".to_string(), 200));
+        check_roundtrip(request, r#"{"position":[51.5,-0.12],"status":["GET",200]}"#);
+
+        let wide = (
+            1u8,
+            2i8,
+            3u16,
+            4i16,
+            5u32,
+            6i32,
+            7u64,
+            8i64,
+            9.5f32,
+            10.5f64,
+            true,
+            'c',
+            "s",
+            (),
+            [0u8; 0],
+            Some(16),
+        );
+        let encoded = serializer::encode(wide);
+        assert_eq!(
+            encoded,
+            r#"[1,2,3,4,5,6,7,8,9.5,10.5,true,"c","s",null,[],16]"#
+        );
+
+        assert!(serializer::decode::<[u8; 3]>("[1, 2]".to_string()).is_err());
+        assert!(serializer::decode::<[u8; 1]>("[1, 2]".to_string()).is_err());
+        assert!(serializer::decode::<(u8, u8)>("[1]".to_string()).is_err());
+        assert!(serializer::decode::<(u8, u8)>("[1, 2, [3]]".to_string()).is_err());
+        let value: mapper::Value = "[1, 2, 3]".parse().unwrap();
+        assert!(<(u8, u8) as serializer::Deserialize>::deserialize(Some(&value)).is_err());
+        assert_eq!(
+            serializer::decode::<(u8, [i8; 2])>("[1, [-2, 3]]".to_string()).unwrap(),
+            (1, [-2, 3])
+        );
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Update {
+        pub level: Option<i32>,
+        pub label: Option<Option<String>>,
+    }
+
+    #[test]
+    pub fn test_option_null() {
+        let decode = decode_both::<Update>;
+
+        let update = decode(r#"{"level": null, "label": null}"#);
+        assert_eq!(update.level, None);
+        assert_eq!(update.label, Some(None));
+        assert_eq!(
+            serializer::encode(&update),
+            r#"{"label":null,"level":null}"#
+        );
+
+        let update = decode(r#"{"label": "x"}"#);
+        assert_eq!(update.label, Some(Some("x".to_string())));
+        let update = decode("{}");
+        assert_eq!(update.label, None);
+        assert_eq!(serializer::encode(&update), r#"{"level":null}"#);
+        assert_eq!(
+            serializer::Serialize::serialize(&update).encode_json(),
+            r#"{"level":null}"#
+        );
+
+        for update in [
+            Update {
+                level: Some(3),
+                label: None,
+            },
+            Update {
+                level: None,
+                label: Some(None),
+            },
+        ] {
+            assert_eq!(decode(&serializer::encode(&update)), update);
+        }
+
+        let values: Vec<Option<u8>> = serializer::decode("[1, null]".to_string()).unwrap();
+        assert_eq!(values, [Some(1), None]);
+        assert!(serializer::decode::<u8>("null".to_string()).is_err());
+    }
+
+    #[test]
+    pub fn test_strict_types() {
+        use serializer::{DecodeError, JsonType};
+
+        let invalid = |path: &str, expected, found| DecodeError::InvalidType {
+            path: path.to_string(),
+            expected,
+            found,
+        };
+        let error = serializer::decode::<A>(r#"{"aJson": 1, "b": 42}"#.to_string()).unwrap_err();
+        assert_eq!(error, invalid("$.b", JsonType::String, JsonType::Number));
+        let error = serializer::decode::<A>(r#"{"aJson": "1", "b": "x"}"#.to_string()).unwrap_err();
+        assert_eq!(
+            error,
+            invalid("$.aJson", JsonType::Number, JsonType::String)
+        );
+        let error = serializer::decode::<bool>("1".to_string()).unwrap_err();
+        assert_eq!(error, invalid("$", JsonType::Bool, JsonType::Number));
+        let error = serializer::decode::<char>("7".to_string()).unwrap_err();
+        assert_eq!(error, invalid("$", JsonType::String, JsonType::Number));
+        let error = serializer::decode::<String>("true".to_string()).unwrap_err();
+        assert_eq!(error, invalid("$", JsonType::String, JsonType::Bool));
+        let error = serializer::decode::<Vec<u8>>(r#"{"a": 1}"#.to_string()).unwrap_err();
+        assert_eq!(error, invalid("$", JsonType::Array, JsonType::Object));
+        let error = serializer::decode::<C>("[]".to_string()).unwrap_err();
+        assert_eq!(error, invalid("$", JsonType::Object, JsonType::Array));
+        let value: mapper::Value = r#"{"a": 1, "b": [3]}"#.parse().unwrap();
+        let error = <C as serializer::Deserialize>::deserialize(Some(&value)).unwrap_err();
+        assert_eq!(error, invalid("$.b[0]", JsonType::Object, JsonType::Number));
+        let error = serializer::decode::<f64>(r#""1.5""#.to_string()).unwrap_err();
+        assert_eq!(error, invalid("$", JsonType::Number, JsonType::String));
+
+        assert_eq!(serializer::decode::<f32>("2".to_string()).unwrap(), 2.0);
+        assert_eq!(
+            serializer::decode::<char>(r#""é""#.to_string()).unwrap(),
+            'é'
+        );
+        assert!(serializer::decode::<char>(r#""ab""#.to_string()).is_err());
     }
 }
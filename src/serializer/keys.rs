@@ -0,0 +1,253 @@
+use crate::formatter;
+use crate::serializer::DecodeError;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+/// Converts map keys to and from the strings JSON requires for object member names.
+///
+/// `#[derive(JsonKey)]` implements it for newtypes by forwarding to the type they
+/// wrap, which by hand reads:
+///
+/// ```
+/// use std::borrow::Cow;
+/// use tiny_json_rs::serializer::{DecodeError, JsonKey};
+///
+/// #[derive(PartialEq, Eq, PartialOrd, Ord)]
+/// struct DeviceId(u32);
+///
+/// impl JsonKey for DeviceId {
+///     fn to_key(&self) -> Cow<'_, str> {
+///         self.0.to_key()
+///     }
+///
+///     fn from_key(key: &str) -> Result<Self, DecodeError> {
+///         u32::from_key(key).map(DeviceId)
+///     }
+/// }
+/// ```
+pub trait JsonKey: Sized {
+    /// Whether `Ord` orders keys the same way as their `to_key` text, which lets a
+    /// `BTreeMap` write its members without sorting them first.
+    const SORTED_AS_KEY: bool = false;
+
+    fn to_key(&self) -> Cow<'_, str>;
+    fn from_key(key: &str) -> Result<Self, DecodeError>;
+}
+
+fn key_error<T>(key: &str) -> DecodeError {
+    DecodeError::invalid_value::<T>(key)
+}
+
+impl JsonKey for String {
+    const SORTED_AS_KEY: bool = true;
+
+    fn to_key(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+
+    fn from_key(key: &str) -> Result<Self, DecodeError> {
+        Ok(key.to_string())
+    }
+}
+
+impl JsonKey for Box<str> {
+    const SORTED_AS_KEY: bool = true;
+
+    fn to_key(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+
+    fn from_key(key: &str) -> Result<Self, DecodeError> {
+        Ok(Box::from(key))
+    }
+}
+
+impl JsonKey for Rc<str> {
+    const SORTED_AS_KEY: bool = true;
+
+    fn to_key(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+
+    fn from_key(key: &str) -> Result<Self, DecodeError> {
+        Ok(Rc::from(key))
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl JsonKey for Arc<str> {
+    const SORTED_AS_KEY: bool = true;
+
+    fn to_key(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+
+    fn from_key(key: &str) -> Result<Self, DecodeError> {
+        Ok(Arc::from(key))
+    }
+}
+
+impl JsonKey for char {
+    const SORTED_AS_KEY: bool = true;
+
+    fn to_key(&self) -> Cow<'_, str> {
+        Cow::Owned(self.to_string())
+    }
+
+    fn from_key(key: &str) -> Result<Self, DecodeError> {
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Ok(ch),
+            _ => Err(key_error::<char>(key)),
+        }
+    }
+}
+
+impl JsonKey for bool {
+    const SORTED_AS_KEY: bool = true;
+
+    fn to_key(&self) -> Cow<'_, str> {
+        Cow::Borrowed(if *self { "true" } else { "false" })
+    }
+
+    fn from_key(key: &str) -> Result<Self, DecodeError> {
+        key.parse().map_err(|_| key_error::<bool>(key))
+    }
+}
+
+macro_rules! integer_key {
+    ($($int:ty => $write:ident as $wide:ty),*) => {$(
+        impl JsonKey for $int {
+            fn to_key(&self) -> Cow<'_, str> {
+                let mut key = String::new();
+                // Writing into a String cannot fail
+                let _ = formatter::$write(&mut key, *self as $wide);
+                Cow::Owned(key)
+            }
+
+            /// Only the form `to_key` writes is accepted, so that `"+5"` or `"05"`
+            /// can't stand for a key already present as `"5"`.
+            fn from_key(key: &str) -> Result<Self, DecodeError> {
+                match key.parse::<$int>() {
+                    Ok(int) if int.to_key() == key => Ok(int),
+                    _ => Err(key_error::<$int>(key)),
+                }
+            }
+        }
+    )*};
+}
+
+integer_key!(
+    u8 => write_u64 as u64,
+    u16 => write_u64 as u64,
+    u32 => write_u64 as u64,
+    u64 => write_u64 as u64,
+    u128 => write_u128 as u128,
+    usize => write_u64 as u64,
+    i8 => write_i64 as i64,
+    i16 => write_i64 as i64,
+    i32 => write_i64 as i64,
+    i64 => write_i64 as i64,
+    i128 => write_i128 as i128,
+    isize => write_i64 as i64
+);
+
+#[cfg(test)]
+pub mod test {
+    use crate::alloc::borrow::ToOwned;
+    use crate::mapper;
+    use crate::serializer;
+    use crate::serializer::test::decode_both;
+    use crate::{Deserialize, Serialize};
+    use alloc::collections::{BTreeMap, BTreeSet, LinkedList, VecDeque};
+    use alloc::string::{String, ToString};
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct DeviceId(u32);
+
+    impl serializer::JsonKey for DeviceId {
+        fn to_key(&self) -> alloc::borrow::Cow<'_, str> {
+            self.0.to_key()
+        }
+
+        fn from_key(key: &str) -> Result<Self, serializer::DecodeError> {
+            u32::from_key(key).map(DeviceId)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Inventory {
+        pub names: BTreeMap<DeviceId, String>,
+        pub grid: BTreeMap<char, BTreeMap<i8, bool>>,
+        pub tags: BTreeSet<String>,
+        pub queue: VecDeque<u8>,
+        pub log: LinkedList<String>,
+    }
+
+    #[test]
+    pub fn test_collections() {
+        const JSON: &str = r#"{"names": {"10": "hub", "2": "lamp"}, "grid": {"a": {"-1": true}}, "tags": ["b", "a", "b"], "queue": [3, 1], "log": []}"#;
+        let inventory: Inventory = decode_both(JSON);
+        assert_eq!(inventory.names[&DeviceId(2)], "lamp");
+        assert!(inventory.grid[&'a'][&-1]);
+        assert_eq!(inventory.tags.len(), 2);
+        assert_eq!(inventory.queue.front(), Some(&3));
+
+        let encoded = serializer::encode(&inventory);
+        assert_eq!(
+            encoded,
+            r#"{"grid":{"a":{"-1":true}},"log":[],"names":{"10":"hub","2":"lamp"},"queue":[3,1],"tags":["a","b"]}"#
+        );
+        assert_eq!(decode_both::<Inventory>(&encoded), inventory);
+
+        let heap: alloc::collections::BinaryHeap<u8> =
+            serializer::decode("[2, 9, 4]".to_string()).unwrap();
+        assert_eq!(heap.into_sorted_vec(), [2, 4, 9]);
+        assert!(serializer::decode::<BTreeMap<u8, u8>>(r#"{"x": 1}"#.to_string()).is_err());
+        assert!(serializer::decode::<BTreeMap<char, u8>>(r#"{"ab": 1}"#.to_string()).is_err());
+
+        // Integer keys must be written the way they encode, so none can collide
+        for key in ["+5", "05", "-0", " 5", "5.0"] {
+            let json = alloc::format!(r#"{{"5": 1, "{}": 2}}"#, key);
+            let error = serializer::decode::<BTreeMap<i32, u8>>(json.clone()).unwrap_err();
+            assert_eq!(error.path(), Some(alloc::format!("$['{}']", key).as_str()));
+            assert!(serializer::decode::<BTreeMap<DeviceId, u8>>(json).is_err());
+        }
+        let keys: BTreeMap<i128, u8> = serializer::decode(
+            r#"{"-170141183460469231731687303715884105728": 1, "0": 2}"#.to_string(),
+        )
+        .unwrap();
+        assert_eq!(keys.len(), 2);
+
+        // Keys that sort like their text are streamed in map order, the rest sorted
+        let letters: BTreeMap<char, bool> = [('é', true), ('z', false), ('A', true)].into();
+        let flags: BTreeMap<bool, i8> = [(true, 1), (false, -1)].into();
+        let mut streamed = String::new();
+        crate::encode_to((&letters, &flags), &mut streamed).unwrap();
+        assert_eq!(
+            streamed,
+            r#"[{"A":true,"z":false,"é":true},{"false":-1,"true":1}]"#
+        );
+        assert_eq!(
+            streamed,
+            serializer::Serialize::serialize(&(&letters, &flags)).to_string()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    pub fn test_hash_collections() {
+        use std::collections::{HashMap, HashSet};
+
+        let map: HashMap<i64, HashSet<String>> =
+            serializer::decode(r#"{"-5": ["x", "x"], "7": []}"#.to_string()).unwrap();
+        assert_eq!(map[&-5].len(), 1);
+        assert!(map[&7].is_empty());
+        let encoded: mapper::Value = serializer::encode(&map).parse().unwrap();
+        assert_eq!(encoded.to_string(), r#"{"-5":["x"],"7":[]}"#);
+    }
+}
@@ -1,26 +1,317 @@
+#[cfg(test)]
+mod derive_test;
 pub mod deserializers;
+pub mod keys;
 pub mod serializers;
 
+use crate::formatter::{self, EncodeOptions, JsonSerializer, PrettyConfig};
+use crate::jsonpath::child_path;
 pub use crate::lexer::{Lexer, LexerError, Token};
-pub use crate::mapper::{Mapper, MapperError, Value};
+pub use crate::mapper::{JsonType, Mapper, MapperError, Value};
 use alloc::format;
-use alloc::string::String;
+use alloc::string::{String, ToString};
 use core::str::FromStr;
+pub use keys::JsonKey;
+
+// Named by derived code, which can't rely on the deriving crate importing them
+pub use alloc::borrow::Cow;
+pub use alloc::vec::Vec;
+
+/// Conversions between JSON types that decoding may apply when the type of a value
+/// does not match the Rust type it is read into. Decoding is strict by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Coercion {
+    /// Read numbers from strings such as `"42"`.
+    pub numbers_from_strings: bool,
+    /// Read booleans from the strings `"true"` and `"false"`.
+    pub bools_from_strings: bool,
+    /// Read booleans from the numbers `1` and `0`.
+    pub bools_from_numbers: bool,
+    /// Read `i128` and `u128` from strings, as written with
+    /// [`EncodeOptions::int128_as_string`](crate::formatter::EncodeOptions::int128_as_string).
+    pub int128_from_strings: bool,
+    /// Read floats from the strings `"NaN"`, `"Infinity"` and `"-Infinity"`, as
+    /// written with [`NonFinite::String`](crate::formatter::NonFinite::String).
+    pub non_finite_from_strings: bool,
+}
+
+impl Coercion {
+    pub const STRICT: Coercion = Coercion {
+        numbers_from_strings: false,
+        bools_from_strings: false,
+        bools_from_numbers: false,
+        int128_from_strings: false,
+        non_finite_from_strings: false,
+    };
+
+    pub const LENIENT: Coercion = Coercion {
+        numbers_from_strings: true,
+        bools_from_strings: true,
+        bools_from_numbers: true,
+        int128_from_strings: true,
+        non_finite_from_strings: true,
+    };
+
+    /// Allows every conversion that either `self` or `other` allows.
+    pub fn union(self, other: Coercion) -> Coercion {
+        Coercion {
+            numbers_from_strings: self.numbers_from_strings || other.numbers_from_strings,
+            bools_from_strings: self.bools_from_strings || other.bools_from_strings,
+            bools_from_numbers: self.bools_from_numbers || other.bools_from_numbers,
+            int128_from_strings: self.int128_from_strings || other.int128_from_strings,
+            non_finite_from_strings: self.non_finite_from_strings || other.non_finite_from_strings,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DecodeOptions {
+    pub coercion: Coercion,
+}
 
 pub trait Deserialize: Sized {
     fn deserialize(value: Option<&Value>) -> Result<Self, DecodeError>;
+
+    /// Like [`Deserialize::deserialize`], applying `coercion` to scalars. Types
+    /// holding other values pass it on to them; the default ignores it.
+    fn deserialize_with(value: Option<&Value>, coercion: Coercion) -> Result<Self, DecodeError> {
+        let _ = coercion;
+        Self::deserialize(value)
+    }
+
+    /// Reads this type straight from the token stream, under the coercion set on
+    /// the mapper. The default parses the next element into a [`Value`] for
+    /// [`Deserialize::deserialize_with`], implementations override it to avoid
+    /// building the tree.
+    fn deserialize_from(mapper: &mut Mapper) -> Result<Self, DecodeError> {
+        let value = mapper.parse_element()?;
+        Self::deserialize_with(Some(&value), mapper.coercion)
+    }
+
+    /// The value an explicit JSON `null` decodes to, for types that have one.
+    /// `Option<T>` decodes `null` to `T`'s null value when there is one and to
+    /// `None` otherwise, so `Option<Option<T>>` reads a missing member as `None`
+    /// and a `null` member as `Some(None)`.
+    fn deserialize_null() -> Option<Self> {
+        None
+    }
+
+    /// Like [`Deserialize::deserialize_with`], but pushes every error it finds onto
+    /// `errors` rather than stopping at the first, returning `None` if there were
+    /// any. The default reports the one error `deserialize_with` returns. Derived
+    /// types, collections, tuples, arrays and the smart pointers override it to keep
+    /// going past a bad member; untagged enums keep the default.
+    fn deserialize_all(
+        value: Option<&Value>,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        Self::deserialize_with(value, coercion)
+            .map_err(|error| errors.push(error))
+            .ok()
+    }
+
+    /// Like [`Deserialize::deserialize_all`] for the members of the object `value`
+    /// other than `tag`, which names the variant of an internally tagged enum
+    /// holding this type. The default decodes a copy of the object without the tag;
+    /// derived structs, the maps and the smart pointers read around it instead.
+    fn deserialize_members(
+        value: &Value,
+        tag: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Self> {
+        let mut value = value.clone();
+        if let Value::Object(object) = &mut value {
+            object.remove(tag);
+        }
+        Self::deserialize_all(Some(&value), coercion, errors)
+    }
 }
 
-pub trait Serialize: Sized {
+pub trait Serialize {
+    /// Whether some value of this type is written as `null`. An `Option` of such a
+    /// type leaves its member out for `None`, keeping it distinct from that `null`.
+    const NULLABLE: bool = false;
+
     fn serialize(&self) -> Value;
+
+    /// Whether a struct member holding this value is left out when encoding.
+    fn is_absent(&self) -> bool {
+        false
+    }
+
+    /// Streams this value into `serializer`. The default goes through
+    /// [`Serialize::serialize`], implementations override it to skip building a
+    /// [`Value`] tree.
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.serialize().serialize_to(serializer)
+    }
+}
+
+/// Marks types that always serialize to a JSON object, as the newtype variants of an
+/// internally tagged enum must so the tag has somewhere to go. Derived structs with
+/// named fields, internally and adjacently tagged enums and the maps implement it.
+pub trait SerializeObject: Serialize {}
+
+/// A leaf value handed to a [`Serializer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar<'a> {
+    Null,
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    /// A numeric literal that is already formatted, written out verbatim.
+    Number(&'a str),
+    Str(&'a str),
+}
+
+/// A sink receiving a document as a flat sequence of events. Every `begin_object`
+/// and `begin_array` is closed by a matching `end`, and inside an object each
+/// member is a `key` followed by a single value.
+pub trait Serializer {
+    type Error;
+
+    fn begin_object(&mut self) -> Result<(), Self::Error>;
+    fn begin_array(&mut self) -> Result<(), Self::Error>;
+    fn key(&mut self, key: &str) -> Result<(), Self::Error>;
+    fn value(&mut self, value: Scalar<'_>) -> Result<(), Self::Error>;
+    fn end(&mut self) -> Result<(), Self::Error>;
+
+    /// The error for a value the document can't hold, such as the content of an
+    /// internally tagged variant that is not an object.
+    fn error(message: &'static str) -> Self::Error;
 }
 
-#[derive(Debug)]
+/// Errors raised while decoding. Apart from syntax errors, each carries the
+/// JSONPath of the value it concerns, such as `$.b[1].aJson`.
+#[derive(Debug, PartialEq)]
 pub enum DecodeError {
     MapperError(MapperError),
     LexerError(LexerError),
-    UnexpectedType,
-    ParseError(String),
+    /// A value is absent where one is required, as when a type is decoded from
+    /// nothing rather than from a member of an object.
+    MissingValue {
+        path: String,
+    },
+    /// A member the target type requires is absent; `path` points at the member.
+    MissingField {
+        path: String,
+        field: String,
+    },
+    InvalidType {
+        path: String,
+        expected: JsonType,
+        found: JsonType,
+    },
+    /// A value of the right JSON type that the target type cannot hold, such as
+    /// `300` for a `u8`. `expected` names the target type.
+    InvalidValue {
+        path: String,
+        value: String,
+        expected: &'static str,
+    },
+    UnknownField {
+        path: String,
+        field: String,
+    },
+    InvalidLength {
+        path: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl DecodeError {
+    /// The error for a value that is absent where one is required. When the
+    /// value belongs to a member, the enclosing object's `get_value` reports a
+    /// [`DecodeError::MissingField`] instead.
+    pub fn missing_value() -> Self {
+        DecodeError::MissingValue {
+            path: String::from("$"),
+        }
+    }
+
+    pub fn missing_field(field: &str) -> Self {
+        DecodeError::MissingField {
+            path: String::from("$"),
+            field: field.to_string(),
+        }
+        .at_field(field)
+    }
+
+    pub fn unknown_field(field: &str) -> Self {
+        DecodeError::UnknownField {
+            path: String::from("$"),
+            field: field.to_string(),
+        }
+        .at_field(field)
+    }
+
+    pub fn invalid_type(expected: JsonType, found: JsonType) -> Self {
+        DecodeError::InvalidType {
+            path: String::from("$"),
+            expected,
+            found,
+        }
+    }
+
+    pub fn invalid_value<T: ?Sized>(value: &str) -> Self {
+        DecodeError::InvalidValue {
+            path: String::from("$"),
+            value: value.to_string(),
+            expected: core::any::type_name::<T>(),
+        }
+    }
+
+    pub fn invalid_length(expected: usize, found: usize) -> Self {
+        DecodeError::InvalidLength {
+            path: String::from("$"),
+            expected,
+            found,
+        }
+    }
+
+    /// The JSONPath of the value the error concerns, if it has one.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            DecodeError::MapperError(_) | DecodeError::LexerError(_) => None,
+            DecodeError::MissingValue { path }
+            | DecodeError::MissingField { path, .. }
+            | DecodeError::InvalidType { path, .. }
+            | DecodeError::InvalidValue { path, .. }
+            | DecodeError::UnknownField { path, .. }
+            | DecodeError::InvalidLength { path, .. } => Some(path),
+        }
+    }
+
+    fn prefix(mut self, selector: &str) -> Self {
+        match &mut self {
+            DecodeError::MapperError(_) | DecodeError::LexerError(_) => {}
+            DecodeError::MissingValue { path }
+            | DecodeError::MissingField { path, .. }
+            | DecodeError::InvalidType { path, .. }
+            | DecodeError::InvalidValue { path, .. }
+            | DecodeError::UnknownField { path, .. }
+            | DecodeError::InvalidLength { path, .. } => path.insert_str(1, selector),
+        }
+        self
+    }
+
+    /// Moves the error into the member `field` of an object, as it propagates out
+    /// of the member's value.
+    pub fn at_field(self, field: &str) -> Self {
+        self.prefix(&child_path("", field))
+    }
+
+    /// Moves the error into the element `index` of an array.
+    pub fn at_index(self, index: usize) -> Self {
+        self.prefix(&format!("[{}]", index))
+    }
 }
 
 impl From<MapperError> for DecodeError {
@@ -40,18 +331,30 @@ impl Token {
     where
         T: FromStr,
     {
-        T::from_str(&self.literal).map_err(|_| {
-            DecodeError::ParseError(format!(
-                "Could not parse {} to {}",
-                self.literal,
-                core::any::type_name::<T>()
-            ))
-        })
+        T::from_str(&self.literal).map_err(|_| DecodeError::invalid_value::<T>(&self.literal))
+    }
+}
+
+impl Mapper {
+    /// Fails with [`DecodeError::InvalidType`] unless the next value of the stream
+    /// is of the `expected` type. Malformed input is left for the parser to report.
+    pub fn expect_type(&mut self, expected: JsonType) -> Result<(), DecodeError> {
+        match self.next_type() {
+            Some(found) if found != expected => Err(DecodeError::invalid_type(expected, found)),
+            _ => Ok(()),
+        }
     }
 }
 
 impl Value {
     pub fn get_value<T>(&self, key: &str) -> Result<T, DecodeError>
+    where
+        T: Deserialize,
+    {
+        self.get_value_with(key, Coercion::STRICT)
+    }
+
+    pub fn get_value_with<T>(&self, key: &str, coercion: Coercion) -> Result<T, DecodeError>
     where
         T: Deserialize,
     {
@@ -60,75 +363,168 @@ impl Value {
             _ => None,
         };
 
-        let res = T::deserialize(option_val)?;
+        T::deserialize_with(option_val, coercion).map_err(|error| match option_val {
+            None => DecodeError::missing_field(key),
+            Some(_) => error.at_field(key),
+        })
+    }
+
+    /// Like [`Value::get_value_with`], collecting errors as
+    /// [`Deserialize::deserialize_all`] does.
+    pub fn get_value_all<T>(
+        &self,
+        key: &str,
+        coercion: Coercion,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<T>
+    where
+        T: Deserialize,
+    {
+        let option_val = match self {
+            Value::Object(object) => object.get(key),
+            _ => None,
+        };
 
-        Ok(res)
+        let mut found = Vec::new();
+        let res = T::deserialize_all(option_val, coercion, &mut found);
+        match option_val {
+            None if !found.is_empty() => errors.push(DecodeError::missing_field(key)),
+            _ => errors.extend(found.into_iter().map(|error| error.at_field(key))),
+        }
+        res
     }
 
     pub fn encode_json(&self) -> String {
+        self.to_string()
+    }
+
+    /// Streams the compact JSON encoding of this value into `out`.
+    pub fn encode_to<W: core::fmt::Write>(&self, out: &mut W) -> core::fmt::Result {
+        formatter::write_compact(self, out, &EncodeOptions::default())
+    }
+
+    /// Fails only when `config.options` rejects a value, as [`encode_with`] does.
+    pub fn encode_pretty(&self, config: &PrettyConfig) -> Result<String, core::fmt::Error> {
         let mut output = String::new();
-        match self {
-            Value::Object(object) => {
-                output += "{";
-                let mut first = true;
-                for (key, value) in object {
-                    if !first {
-                        output += ",";
-                    }
-                    first = false;
-                    output += &format!("\"{}\":{}", key, value.encode_json());
-                }
-                output += "}";
-            }
-            Value::Token(t) => match t.token_type {
-                crate::lexer::TokenType::String(_) => {
-                    output += &format!("\"{}\"", t.literal);
-                }
-                _ => {
-                    output += &format!("{}", t.literal);
-                }
-            },
-            Value::Array(a) => {
-                output += "[";
-                let mut first = true;
-                for value in a {
-                    if !first {
-                        output += ",";
-                    }
-                    first = false;
-                    output += &format!("{}", value.encode_json());
-                }
-                output += "]";
-            }
-        }
+        formatter::write_pretty(self, &mut output, config)?;
+        Ok(output)
+    }
+}
+
+impl FromStr for Value {
+    type Err = DecodeError;
 
-        output
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut lexer = Lexer::new(input.to_string());
+        let tokens = lexer.tokenize()?;
+        let mut mapper = Mapper::new(tokens);
+        Ok(mapper.parse()?)
     }
 }
 
 pub fn decode<T>(input_str: String) -> Result<T, DecodeError>
+where
+    T: Deserialize,
+{
+    decode_with(input_str, &DecodeOptions::default())
+}
+
+pub fn decode_with<T>(input_str: String, options: &DecodeOptions) -> Result<T, DecodeError>
 where
     T: Deserialize,
 {
     let mut lexer = Lexer::new(input_str);
     let tokens = lexer.tokenize()?;
     let mut mapper = Mapper::new(tokens);
-    let object = mapper.parse_object()?;
-    let value = Value::Object(object);
-    Ok(T::deserialize(Some(&value))?)
+    mapper.coercion = options.coercion;
+    let result = T::deserialize_from(&mut mapper)?;
+    mapper.finish()?;
+    Ok(result)
+}
+
+/// Decodes `input`, reporting every missing or invalid member instead of only the
+/// first. Malformed JSON fails with the single syntax error.
+pub fn decode_all<T>(input_str: String) -> Result<T, Vec<DecodeError>>
+where
+    T: Deserialize,
+{
+    decode_all_with(input_str, &DecodeOptions::default())
+}
+
+pub fn decode_all_with<T>(input_str: String, options: &DecodeOptions) -> Result<T, Vec<DecodeError>>
+where
+    T: Deserialize,
+{
+    let value = Value::from_str(&input_str).map_err(|error| alloc::vec![error])?;
+    let mut errors = Vec::new();
+    match T::deserialize_all(Some(&value), options.coercion, &mut errors) {
+        Some(result) if errors.is_empty() => Ok(result),
+        _ => Err(errors),
+    }
 }
 
 pub fn encode<T>(input: T) -> String
 where
     T: Serialize,
 {
-    input.serialize().encode_json()
+    let mut output = String::new();
+    // The default options reject no value and a String never fails, which leaves only
+    // tagged content that isn't an object, and `SerializeObject` rules that out
+    encode_to(input, &mut output).expect("a SerializeObject type serialized to a non-object");
+    output
+}
+
+pub fn encode_to<T, W>(input: T, out: &mut W) -> core::fmt::Result
+where
+    T: Serialize,
+    W: core::fmt::Write,
+{
+    encode_to_with(input, out, &EncodeOptions::default())
+}
+
+/// Encodes `input` under `options`. Fails only when the options reject a value,
+/// such as a NaN with [`formatter::NonFinite::Error`].
+pub fn encode_with<T>(input: T, options: &EncodeOptions) -> Result<String, core::fmt::Error>
+where
+    T: Serialize,
+{
+    let mut output = String::new();
+    encode_to_with(input, &mut output, options)?;
+    Ok(output)
+}
+
+pub fn encode_to_with<T, W>(input: T, out: &mut W, options: &EncodeOptions) -> core::fmt::Result
+where
+    T: Serialize,
+    W: core::fmt::Write,
+{
+    input.serialize_to(&mut JsonSerializer::with_options(out, options))
+}
+
+#[cfg(feature = "std")]
+pub fn encode_to_writer<T, W>(input: T, writer: W) -> std::io::Result<()>
+where
+    T: Serialize,
+    W: std::io::Write,
+{
+    let mut out = formatter::IoWriter::new(writer);
+    let result = encode_to(input, &mut out);
+    out.finish(result)
+}
+
+pub fn encode_pretty<T>(input: T, config: &PrettyConfig) -> Result<String, core::fmt::Error>
+where
+    T: Serialize,
+{
+    input.serialize().encode_pretty(config)
 }
 
 #[cfg(test)]
 pub mod test {
     use crate::{Deserialize, Serialize};
+    use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
     use alloc::string::{String, ToString};
+    use alloc::vec;
     use alloc::vec::Vec;
 
     use crate::alloc::borrow::ToOwned;
@@ -154,6 +550,20 @@ pub mod test {
         pub b: Vec<A>,
     }
 
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    pub struct D {
+        pub z: Option<char>,
+        pub c: C,
+        pub raw: mapper::Value,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Node {
+        pub label: alloc::rc::Rc<String>,
+        pub weight: core::cell::Cell<u8>,
+        pub next: Option<alloc::boxed::Box<Node>>,
+    }
+
     #[test]
     pub fn test_deserialize() {
         const JSON: &str = r#"
@@ -218,4 +628,411 @@ pub mod test {
         assert_eq!(a.b[1].a, 2);
         assert_eq!(a.b[1].b, "World");
     }
+
+    #[test]
+    pub fn test_value_from_str() {
+        let value: mapper::Value =
+            r#"[1, "caf\u00e9 \ud83d\ude00", {"a b": true}]"#.parse().unwrap();
+        assert_eq!(value.to_string(), r#"[1,"café 😀",{"a b":true}]"#);
+        assert_eq!(value.to_string().parse::<mapper::Value>().unwrap(), value);
+
+        let value: mapper::Value = "-42".parse().unwrap();
+        assert_eq!(value.to_string(), "-42");
+
+        assert!("{\"a\": 1".parse::<mapper::Value>().is_err());
+        assert!("[1] 2".parse::<mapper::Value>().is_err());
+    }
+
+    #[test]
+    pub fn test_encode_to() {
+        struct Uart {
+            buffer: [u8; 32],
+            len: usize,
+        }
+
+        impl core::fmt::Write for Uart {
+            fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                let end = self.len + s.len();
+                if end > self.buffer.len() {
+                    return Err(core::fmt::Error);
+                }
+                self.buffer[self.len..end].copy_from_slice(s.as_bytes());
+                self.len = end;
+                Ok(())
+            }
+        }
+
+        let a = A {
+            a: 1,
+            b: "Hello".to_string(),
+        };
+        let mut uart = Uart {
+            buffer: [0; 32],
+            len: 0,
+        };
+        super::encode_to(a, &mut uart).unwrap();
+        assert_eq!(&uart.buffer[..uart.len], br#"{"aJson":1,"b":"Hello"}"#);
+
+        let long = B {
+            a: 1,
+            b: ["x".repeat(40)].to_vec(),
+        };
+        assert!(super::encode_to(long, &mut uart).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    pub fn test_encode_to_writer() {
+        let mut output = std::vec::Vec::new();
+        let a = A {
+            a: -3,
+            b: "Hi".to_string(),
+        };
+        super::encode_to_writer(a, &mut output).unwrap();
+        assert_eq!(output, br#"{"aJson":-3,"b":"Hi"}"#);
+    }
+
+    /// Decodes `json` both by streaming and through a [`mapper::Value`], checking
+    /// the two agree.
+    pub(crate) fn decode_both<T>(json: &str) -> T
+    where
+        T: serializer::Deserialize + PartialEq + core::fmt::Debug,
+    {
+        let streamed = super::decode::<T>(json.to_string()).unwrap();
+        let value: mapper::Value = json.parse().unwrap();
+        let from_value = T::deserialize(Some(&value)).unwrap();
+        assert_eq!(streamed, from_value);
+        assert_eq!(
+            super::decode_all::<T>(json.to_string()).unwrap(),
+            from_value
+        );
+        streamed
+    }
+
+    /// Checks that `value` encodes to `json`, by streaming and through the tree,
+    /// and that `json` decodes back to `value` both ways.
+    pub(crate) fn check_roundtrip<T>(value: T, json: &str)
+    where
+        T: serializer::Serialize + serializer::Deserialize + PartialEq + core::fmt::Debug,
+    {
+        assert_eq!(super::encode(&value), json);
+        assert_eq!(serializer::Serialize::serialize(&value).encode_json(), json);
+        assert_eq!(decode_both::<T>(json), value);
+    }
+
+    fn sample_d() -> D {
+        D {
+            z: Some('"'),
+            c: C {
+                a: -1,
+                b: vec![A {
+                    a: 7,
+                    b: "x".to_string(),
+                }],
+            },
+            raw: r#"{"n": [1.50, null, false]}"#.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    pub fn test_encode_streaming() {
+        let streamed = super::encode(sample_d());
+        assert_eq!(
+            streamed,
+            r#"{"c":{"a":-1,"b":[{"aJson":7,"b":"x"}]},"raw":{"n":[1.50,null,false]},"z":"\""}"#
+        );
+        assert_eq!(
+            serializer::Serialize::serialize(&sample_d()).encode_json(),
+            streamed
+        );
+
+        let pretty = super::encode_pretty(sample_d(), &super::PrettyConfig::default()).unwrap();
+        let compacted = pretty.replace(['\n', ' '], "");
+        assert_eq!(compacted, streamed);
+
+        let decoded: D = super::decode(streamed).unwrap();
+        assert_eq!(decoded, sample_d());
+    }
+
+    #[test]
+    pub fn test_encode_deep_nesting() {
+        let mut node = None;
+        for weight in 0..300 {
+            node = Some(alloc::boxed::Box::new(Node {
+                label: alloc::rc::Rc::new("n".to_string()),
+                weight: core::cell::Cell::new((weight % 256) as u8),
+                next: node,
+            }));
+        }
+
+        let json = super::encode(&node);
+        let value: mapper::Value = json.parse().unwrap();
+        assert_eq!(value.encode_json(), json);
+        assert_eq!(serializer::Serialize::serialize(&node).encode_json(), json);
+    }
+
+    #[test]
+    pub fn test_decode_streaming() {
+        const JSON: &str = r#"
+        {
+            "extra": {"deep": [1, {"x": []}], "s": "}"},
+            "b": [{"b": "Hello", "aJson": 1, "unused": null}, {"aJson": 2, "b": "World"}],
+            "a": 5
+        }"#;
+
+        let streamed: C = decode_both(JSON);
+        assert_eq!(streamed.b[1].b, "World");
+
+        let missing: B = super::decode(r#"{"a": 1}"#.to_string()).unwrap();
+        assert!(missing.b.is_empty());
+        assert!(super::decode::<A>(r#"{"aJson": 1, "b": "x"} 1"#.to_string()).is_err());
+        assert!(super::decode::<A>(r#"{"aJson": 1 "b": "x"}"#.to_string()).is_err());
+        assert!(super::decode::<B>(r#"{"a": 1, "b": ["x" "y"]}"#.to_string()).is_err());
+        assert!(super::decode::<A>(r#"{"b": "x"}"#.to_string()).is_err());
+
+        // Skipped members are still checked, so both paths reject the same documents
+        for json in [
+            r#"{"aJson":1,"b":"x","extra":[1 2]}"#,
+            r#"{"aJson":1,"b":"x","extra":{"k" 1 2}}"#,
+            r#"{"aJson":1,"b":"x","extra":{1:2}}"#,
+            r#"{"aJson":1,"b":"x","extra":[1,]}"#,
+            r#"{"aJson":1,"b":"x","extra":{"k":1,}}"#,
+            r#"{"aJson":1,"b":"x","extra":[1}}"#,
+        ] {
+            assert!(json.parse::<mapper::Value>().is_err(), "{}", json);
+            assert!(super::decode::<A>(json.to_string()).is_err(), "{}", json);
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Reading {
+        #[Coerce]
+        pub value: f64,
+        #[Coerce]
+        pub valid: Vec<bool>,
+        pub count: u32,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Celsius(#[Coerce] f64);
+
+    #[test]
+    pub fn test_coercion() {
+        use super::{Coercion, DecodeError, DecodeOptions, JsonType};
+
+        const JSON: &str = r#"{"value": "21.5", "valid": ["true", 0, false], "count": 3}"#;
+        let expected = Reading {
+            value: 21.5,
+            valid: vec![true, false, false],
+            count: 3,
+        };
+        assert_eq!(decode_both::<Reading>(JSON), expected);
+        assert_eq!(decode_both::<Celsius>(r#""21.5""#), Celsius(21.5));
+        assert_eq!(
+            decode_both::<Vec<Celsius>>(r#"["1", 2]"#),
+            [Celsius(1.0), Celsius(2.0)]
+        );
+
+        // Only the marked fields are lenient
+        let json = r#"{"value": 1, "valid": [], "count": "3"}"#;
+        assert_eq!(
+            super::decode::<Reading>(json.to_string()).unwrap_err(),
+            DecodeError::InvalidType {
+                path: "$.count".to_string(),
+                expected: JsonType::Number,
+                found: JsonType::String
+            }
+        );
+        let lenient = DecodeOptions {
+            coercion: Coercion::LENIENT,
+        };
+        assert_eq!(
+            super::decode_with::<Reading>(json.to_string(), &lenient)
+                .unwrap()
+                .count,
+            3
+        );
+
+        let numbers_only = DecodeOptions {
+            coercion: Coercion {
+                numbers_from_strings: true,
+                ..Coercion::STRICT
+            },
+        };
+        let values: Vec<i64> =
+            super::decode_with(r#"[1, "-2"]"#.to_string(), &numbers_only).unwrap();
+        assert_eq!(values, [1, -2]);
+        assert!(super::decode_with::<bool>("1".to_string(), &numbers_only).is_err());
+        assert!(super::decode_with::<bool>("2".to_string(), &lenient).is_err());
+        assert!(super::decode_with::<u8>(r#""x""#.to_string(), &lenient).is_err());
+        assert!(super::decode_with::<String>("1".to_string(), &lenient).is_err());
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[DenyUnknownFields]
+    pub struct Strict {
+        pub a: i32,
+        pub pair: (u8, u8),
+    }
+
+    #[test]
+    pub fn test_error_paths() {
+        use super::DecodeError;
+
+        let decode_both = |json: &str| {
+            let value: mapper::Value = json.parse().unwrap();
+            let from_value = <C as serializer::Deserialize>::deserialize(Some(&value));
+            let streamed = super::decode::<C>(json.to_string());
+            assert_eq!(from_value, streamed);
+            streamed.unwrap_err()
+        };
+
+        let error = decode_both(
+            r#"{"a": 1, "b": [{"aJson": 1, "b": ""}, {"aJson": 3000000000, "b": ""}]}"#,
+        );
+        assert_eq!(
+            error,
+            DecodeError::InvalidValue {
+                path: "$.b[1].aJson".to_string(),
+                value: "3000000000".to_string(),
+                expected: "i32",
+            }
+        );
+        assert_eq!(error.path(), Some("$.b[1].aJson"));
+
+        let error = decode_both(r#"{"a": 1, "b": [{"b": ""}]}"#);
+        assert_eq!(
+            error,
+            DecodeError::MissingField {
+                path: "$.b[0].aJson".to_string(),
+                field: "aJson".to_string(),
+            }
+        );
+        // Decoding from nothing is not a missing member
+        let missing = DecodeError::MissingValue {
+            path: "$".to_string(),
+        };
+        assert_eq!(
+            <u8 as serializer::Deserialize>::deserialize(None),
+            Err(missing)
+        );
+        let mut errors = Vec::new();
+        assert_eq!(
+            <Strict as serializer::Deserialize>::deserialize_all(
+                None,
+                super::Coercion::STRICT,
+                &mut errors
+            ),
+            None
+        );
+        assert_eq!(errors, [DecodeError::missing_value()]);
+        assert_eq!(
+            DecodeError::missing_value().at_index(2).path(),
+            Some("$[2]")
+        );
+
+        let error = super::decode::<Strict>(r#"{"a": 1, "pair": [1, 2], "my key": 0}"#.to_string())
+            .unwrap_err();
+        let unknown = DecodeError::UnknownField {
+            path: "$['my key']".to_string(),
+            field: "my key".to_string(),
+        };
+        assert_eq!(error, unknown);
+        let value: mapper::Value = r#"{"a": 1, "pair": [1, 2], "my key": 0}"#.parse().unwrap();
+        let error = <Strict as serializer::Deserialize>::deserialize(Some(&value)).unwrap_err();
+        assert_eq!(error, unknown);
+
+        let error =
+            super::decode::<Strict>(r#"{"a": 1, "pair": [1, 2, 3]}"#.to_string()).unwrap_err();
+        assert_eq!(
+            error,
+            DecodeError::InvalidLength {
+                path: "$.pair".to_string(),
+                expected: 2,
+                found: 3,
+            }
+        );
+        let error =
+            super::decode::<Vec<Strict>>(r#"[{"a": 1, "pair": [1, -2]}]"#.to_string()).unwrap_err();
+        assert_eq!(error.path(), Some("$[0].pair[1]"));
+    }
+
+    #[test]
+    pub fn test_decode_all() {
+        use super::{DecodeError, JsonType};
+
+        let json = r#"{"a": "1", "b": [{"aJson": 1, "b": 2}, {"b": ""}, {"aJson": 2.5, "b": ""}]}"#;
+        let errors = super::decode_all::<C>(json.to_string()).unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                DecodeError::InvalidType {
+                    path: "$.a".to_string(),
+                    expected: JsonType::Number,
+                    found: JsonType::String,
+                },
+                DecodeError::InvalidType {
+                    path: "$.b[0].b".to_string(),
+                    expected: JsonType::String,
+                    found: JsonType::Number,
+                },
+                DecodeError::MissingField {
+                    path: "$.b[1].aJson".to_string(),
+                    field: "aJson".to_string(),
+                },
+                DecodeError::InvalidValue {
+                    path: "$.b[2].aJson".to_string(),
+                    value: "2.5".to_string(),
+                    expected: "i32",
+                },
+            ]
+        );
+
+        let errors = super::decode_all::<BTreeMap<String, Strict>>(
+            r#"{"x": {"a": 1, "pair": [1, 2], "y": 0, "z": 0}}"#.to_string(),
+        )
+        .unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path().unwrap()).collect();
+        assert_eq!(paths, ["$.x.y", "$.x.z"]);
+
+        // Every container keeps going past a bad element
+        let errors = super::decode_all::<(
+            alloc::rc::Rc<BTreeSet<u8>>,
+            [Strict; 1],
+            core::cell::RefCell<VecDeque<A>>,
+        )>(
+            r#"[["x", 1, true], [{"a": 1, "pair": [1, 2], "y": 0}], [{"aJson": "1", "b": 2}]]"#
+                .to_string(),
+        )
+        .unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path().unwrap()).collect();
+        assert_eq!(
+            paths,
+            [
+                "$[0][0]",
+                "$[0][2]",
+                "$[1][0].y",
+                "$[2][0].aJson",
+                "$[2][0].b"
+            ]
+        );
+        let errors = super::decode_all::<[u8; 2]>("[1]".to_string()).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [DecodeError::InvalidLength {
+                expected: 2,
+                found: 1,
+                ..
+            }]
+        ));
+
+        let json = r#"{"a": 1, "b": [{"aJson": 1, "b": ""}]}"#;
+        assert_eq!(
+            super::decode_all::<C>(json.to_string()).unwrap(),
+            super::decode::<C>(json.to_string()).unwrap()
+        );
+        let errors = super::decode_all::<C>("{".to_string()).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path(), None);
+    }
 }
@@ -1,106 +1,229 @@
+use crate::formatter::{self, EncodeOptions};
 use crate::lexer::{StringType, Token, TokenType};
-use crate::mapper::Value;
-use crate::serializer::Serialize;
+use crate::mapper::{Object, Value};
+use crate::serializer::{JsonKey, Scalar, Serialize, SerializeObject, Serializer};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::rc::Rc;
 use alloc::string::{String, ToString};
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+fn unsigned_token(value: u128) -> Value {
+    let mut literal = String::with_capacity(40);
+    // Writing into a String cannot fail
+    let _ = formatter::write_u128(&mut literal, value);
+    Value::Token(Token {
+        token_type: TokenType::Int,
+        literal,
+    })
+}
+
+fn signed_token(value: i128) -> Value {
+    let mut literal = String::with_capacity(40);
+    let _ = formatter::write_i128(&mut literal, value);
+    Value::Token(Token {
+        token_type: TokenType::Int,
+        literal,
+    })
+}
 
 impl Serialize for u8 {
     fn serialize(&self) -> Value {
-        Value::Token(Token {
-            token_type: TokenType::Int,
-            literal: self.to_string(),
-        })
+        unsigned_token(*self as u128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::U64(*self as u64))
     }
 }
 
 impl Serialize for u16 {
     fn serialize(&self) -> Value {
-        Value::Token(Token {
-            token_type: TokenType::Int,
-            literal: self.to_string(),
-        })
+        unsigned_token(*self as u128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::U64(*self as u64))
     }
 }
 
 impl Serialize for u32 {
     fn serialize(&self) -> Value {
-        Value::Token(Token {
-            token_type: TokenType::Int,
-            literal: self.to_string(),
-        })
+        unsigned_token(*self as u128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::U64(*self as u64))
     }
 }
 
 impl Serialize for u64 {
     fn serialize(&self) -> Value {
-        Value::Token(Token {
-            token_type: TokenType::Int,
-            literal: self.to_string(),
-        })
+        unsigned_token(*self as u128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::U64(*self))
     }
 }
 
 impl Serialize for usize {
     fn serialize(&self) -> Value {
-        Value::Token(Token {
-            token_type: TokenType::Int,
-            literal: self.to_string(),
-        })
+        unsigned_token(*self as u128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::U64(*self as u64))
     }
 }
 
 impl Serialize for i8 {
     fn serialize(&self) -> Value {
-        Value::Token(Token {
-            token_type: TokenType::Int,
-            literal: self.to_string(),
-        })
+        signed_token(*self as i128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::I64(*self as i64))
     }
 }
 
 impl Serialize for i16 {
     fn serialize(&self) -> Value {
-        Value::Token(Token {
-            token_type: TokenType::Int,
-            literal: self.to_string(),
-        })
+        signed_token(*self as i128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::I64(*self as i64))
     }
 }
 
 impl Serialize for i32 {
     fn serialize(&self) -> Value {
-        Value::Token(Token {
-            token_type: TokenType::Int,
-            literal: self.to_string(),
-        })
+        signed_token(*self as i128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::I64(*self as i64))
     }
 }
 
 impl Serialize for i64 {
     fn serialize(&self) -> Value {
-        Value::Token(Token {
-            token_type: TokenType::Int,
-            literal: self.to_string(),
-        })
+        signed_token(*self as i128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::I64(*self))
+    }
+}
+
+impl Serialize for isize {
+    fn serialize(&self) -> Value {
+        signed_token(*self as i128)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::I64(*self as i64))
+    }
+}
+
+impl Serialize for u128 {
+    fn serialize(&self) -> Value {
+        unsigned_token(*self)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::U128(*self))
+    }
+}
+
+impl Serialize for i128 {
+    fn serialize(&self) -> Value {
+        signed_token(*self)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::I128(*self))
     }
 }
 
+macro_rules! serialize_non_zero {
+    ($($non_zero:ty),*) => {$(
+        impl Serialize for $non_zero {
+            fn serialize(&self) -> Value {
+                self.get().serialize()
+            }
+
+            fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+                self.get().serialize_to(serializer)
+            }
+        }
+    )*};
+}
+
+serialize_non_zero!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+
 impl Serialize for f32 {
     fn serialize(&self) -> Value {
+        let mut literal = String::new();
+        if self.is_finite() {
+            let _ = formatter::write_f32(&mut literal, *self, &EncodeOptions::default());
+        } else {
+            // Kept by name so the encoder can apply its non-finite policy
+            literal.push_str(formatter::non_finite_name(*self as f64));
+        }
         Value::Token(Token {
             token_type: TokenType::Float,
-            literal: self.to_string(),
+            literal,
         })
     }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::F32(*self))
+    }
 }
 
 impl Serialize for f64 {
     fn serialize(&self) -> Value {
+        let mut literal = String::new();
+        if self.is_finite() {
+            let _ = formatter::write_f64(&mut literal, *self, &EncodeOptions::default());
+        } else {
+            // Kept by name so the encoder can apply its non-finite policy
+            literal.push_str(formatter::non_finite_name(*self));
+        }
         Value::Token(Token {
             token_type: TokenType::Float,
-            literal: self.to_string(),
+            literal,
         })
     }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::F64(*self))
+    }
 }
 
 impl Serialize for bool {
@@ -110,6 +233,10 @@ impl Serialize for bool {
             literal: self.to_string(),
         })
     }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::Bool(*self))
+    }
 }
 
 impl Serialize for String {
@@ -119,6 +246,23 @@ impl Serialize for String {
             literal: self.to_string(),
         })
     }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::Str(self))
+    }
+}
+
+impl Serialize for str {
+    fn serialize(&self) -> Value {
+        Value::Token(Token {
+            token_type: TokenType::String(StringType::ComplexString),
+            literal: self.to_string(),
+        })
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::Str(self))
+    }
 }
 
 impl Serialize for char {
@@ -128,12 +272,22 @@ impl Serialize for char {
             literal: self.to_string(),
         })
     }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::Str(self.encode_utf8(&mut [0; 4])))
+    }
 }
 
 impl<T> Serialize for Option<T>
 where
     T: Serialize,
 {
+    const NULLABLE: bool = true;
+
+    fn is_absent(&self) -> bool {
+        self.is_none() && T::NULLABLE
+    }
+
     fn serialize(&self) -> Value {
         match self {
             Some(val) => val.serialize(),
@@ -143,9 +297,16 @@ where
             }),
         }
     }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        match self {
+            Some(val) => val.serialize_to(serializer),
+            None => serializer.value(Scalar::Null),
+        }
+    }
 }
 
-impl<T> Serialize for Vec<T>
+impl<T> Serialize for [T]
 where
     T: Serialize,
 {
@@ -156,4 +317,520 @@ where
         }
         Value::Array(array)
     }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.begin_array()?;
+        for val in self {
+            val.serialize_to(serializer)?;
+        }
+        serializer.end()
+    }
+}
+
+impl<T, const N: usize> Serialize for [T; N]
+where
+    T: Serialize,
+{
+    fn serialize(&self) -> Value {
+        self.as_slice().serialize()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.as_slice().serialize_to(serializer)
+    }
+}
+
+impl<T> Serialize for Vec<T>
+where
+    T: Serialize,
+{
+    fn serialize(&self) -> Value {
+        self.as_slice().serialize()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.as_slice().serialize_to(serializer)
+    }
+}
+
+const UNTAGGABLE: &str = "internally tagged variants must hold objects";
+
+/// Adds the member naming the variant to the content of a newtype variant of an
+/// internally tagged enum.
+///
+/// # Panics
+///
+/// If the content is not an object, which its [`SerializeObject`] impl promises it is.
+pub fn insert_tag<T>(content: &T, tag: &str, variant: &str) -> Value
+where
+    T: SerializeObject + ?Sized,
+{
+    match content.serialize() {
+        Value::Object(mut object) => {
+            object.insert(tag.to_owned(), variant.serialize());
+            Value::Object(object)
+        }
+        _ => panic!("{}", UNTAGGABLE),
+    }
+}
+
+/// Streams the content of a newtype variant of an internally tagged enum with the
+/// member naming the variant merged in. Content that breaks its [`SerializeObject`]
+/// promise fails through `serializer`.
+pub fn serialize_tagged<T, S>(
+    content: &T,
+    tag: &str,
+    variant: &str,
+    serializer: &mut S,
+) -> Result<(), S::Error>
+where
+    T: SerializeObject + ?Sized,
+    S: Serializer,
+{
+    content.serialize_to(&mut TaggedContent {
+        inner: serializer,
+        tag,
+        variant,
+        depth: 0,
+        tagged: false,
+    })
+}
+
+// Forwards the content's events, writing the tag among the top-level members where
+// its key sorts, so the stream matches `insert_tag`
+struct TaggedContent<'a, S> {
+    inner: &'a mut S,
+    tag: &'a str,
+    variant: &'a str,
+    depth: usize,
+    tagged: bool,
+}
+
+impl<S: Serializer> TaggedContent<'_, S> {
+    fn write_tag(&mut self) -> Result<(), S::Error> {
+        self.tagged = true;
+        self.inner.key(self.tag)?;
+        self.inner.value(Scalar::Str(self.variant))
+    }
+}
+
+impl<S: Serializer> Serializer for TaggedContent<'_, S> {
+    type Error = S::Error;
+
+    fn begin_object(&mut self) -> Result<(), S::Error> {
+        self.depth += 1;
+        self.inner.begin_object()
+    }
+
+    fn begin_array(&mut self) -> Result<(), S::Error> {
+        if self.depth == 0 {
+            return Err(S::error(UNTAGGABLE));
+        }
+        self.depth += 1;
+        self.inner.begin_array()
+    }
+
+    fn key(&mut self, key: &str) -> Result<(), S::Error> {
+        if self.depth == 1 && !self.tagged && key > self.tag {
+            self.write_tag()?;
+        }
+        self.inner.key(key)
+    }
+
+    fn value(&mut self, value: Scalar<'_>) -> Result<(), S::Error> {
+        if self.depth == 0 {
+            return Err(S::error(UNTAGGABLE));
+        }
+        self.inner.value(value)
+    }
+
+    fn end(&mut self) -> Result<(), S::Error> {
+        if self.depth == 1 && !self.tagged {
+            self.write_tag()?;
+        }
+        self.depth -= 1;
+        self.inner.end()
+    }
+
+    fn error(message: &'static str) -> S::Error {
+        S::error(message)
+    }
+}
+
+fn serialize_seq<'a, T, I>(iter: I) -> Value
+where
+    T: Serialize + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    Value::Array(iter.into_iter().map(|val| val.serialize()).collect())
+}
+
+fn serialize_seq_to<'a, T, I, S>(iter: I, serializer: &mut S) -> Result<(), S::Error>
+where
+    T: Serialize + 'a,
+    I: IntoIterator<Item = &'a T>,
+    S: Serializer,
+{
+    serializer.begin_array()?;
+    for val in iter {
+        val.serialize_to(serializer)?;
+    }
+    serializer.end()
+}
+
+fn serialize_map<'a, K, V, I>(iter: I) -> Value
+where
+    K: JsonKey + 'a,
+    V: Serialize + 'a,
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+{
+    let mut object = Object::new();
+    for (key, val) in iter {
+        object.insert(key.to_key().into_owned(), val.serialize());
+    }
+    Value::Object(object)
+}
+
+/// `sorted` says the entries already come in the order of their key text.
+fn serialize_map_to<'a, K, V, I, S>(
+    iter: I,
+    sorted: bool,
+    serializer: &mut S,
+) -> Result<(), S::Error>
+where
+    K: JsonKey + 'a,
+    V: Serialize + 'a,
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+    S: Serializer,
+{
+    let entries = iter.into_iter().map(|(key, val)| (key.to_key(), val));
+    serializer.begin_object()?;
+    if sorted {
+        for (key, val) in entries {
+            serializer.key(&key)?;
+            val.serialize_to(serializer)?;
+        }
+        return serializer.end();
+    }
+
+    // Members go out in the order of their key text, as in an encoded `Value`
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (key, val) in entries {
+        serializer.key(&key)?;
+        val.serialize_to(serializer)?;
+    }
+    serializer.end()
+}
+
+impl<T> Serialize for VecDeque<T>
+where
+    T: Serialize,
+{
+    fn serialize(&self) -> Value {
+        serialize_seq(self)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serialize_seq_to(self, serializer)
+    }
+}
+
+impl<T> Serialize for LinkedList<T>
+where
+    T: Serialize,
+{
+    fn serialize(&self) -> Value {
+        serialize_seq(self)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serialize_seq_to(self, serializer)
+    }
+}
+
+/// Elements are written in the heap's internal order, not sorted.
+impl<T> Serialize for BinaryHeap<T>
+where
+    T: Serialize,
+{
+    fn serialize(&self) -> Value {
+        serialize_seq(self)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serialize_seq_to(self, serializer)
+    }
+}
+
+impl<T> Serialize for BTreeSet<T>
+where
+    T: Serialize,
+{
+    fn serialize(&self) -> Value {
+        serialize_seq(self)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serialize_seq_to(self, serializer)
+    }
+}
+
+impl<K, V> Serialize for BTreeMap<K, V>
+where
+    K: JsonKey,
+    V: Serialize,
+{
+    fn serialize(&self) -> Value {
+        serialize_map(self)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serialize_map_to(self, K::SORTED_AS_KEY, serializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, H> Serialize for HashSet<T, H>
+where
+    T: Serialize,
+{
+    fn serialize(&self) -> Value {
+        serialize_seq(self)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serialize_seq_to(self, serializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, H> Serialize for HashMap<K, V, H>
+where
+    K: JsonKey,
+    V: Serialize,
+{
+    fn serialize(&self) -> Value {
+        serialize_map(self)
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serialize_map_to(self, false, serializer)
+    }
+}
+
+impl<K: JsonKey, V: Serialize> SerializeObject for BTreeMap<K, V> {}
+
+#[cfg(feature = "std")]
+impl<K: JsonKey, V: Serialize, H> SerializeObject for HashMap<K, V, H> {}
+
+impl<T: SerializeObject + ?Sized> SerializeObject for &T {}
+
+impl<T: SerializeObject + ?Sized> SerializeObject for Box<T> {}
+
+impl<T: SerializeObject + ?Sized> SerializeObject for Rc<T> {}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: SerializeObject + ?Sized> SerializeObject for Arc<T> {}
+
+impl<T: SerializeObject + ToOwned + ?Sized> SerializeObject for Cow<'_, T> {}
+
+impl<T: SerializeObject + ?Sized> SerializeObject for RefCell<T> {}
+
+impl<T> Serialize for &T
+where
+    T: Serialize + ?Sized,
+{
+    const NULLABLE: bool = T::NULLABLE;
+
+    fn serialize(&self) -> Value {
+        (**self).serialize()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        (**self).serialize_to(serializer)
+    }
+}
+
+impl<T> Serialize for Box<T>
+where
+    T: Serialize + ?Sized,
+{
+    const NULLABLE: bool = T::NULLABLE;
+
+    fn serialize(&self) -> Value {
+        (**self).serialize()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        (**self).serialize_to(serializer)
+    }
+}
+
+impl<T> Serialize for Rc<T>
+where
+    T: Serialize + ?Sized,
+{
+    const NULLABLE: bool = T::NULLABLE;
+
+    fn serialize(&self) -> Value {
+        (**self).serialize()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        (**self).serialize_to(serializer)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> Serialize for Arc<T>
+where
+    T: Serialize + ?Sized,
+{
+    const NULLABLE: bool = T::NULLABLE;
+
+    fn serialize(&self) -> Value {
+        (**self).serialize()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        (**self).serialize_to(serializer)
+    }
+}
+
+impl<T> Serialize for Cow<'_, T>
+where
+    T: Serialize + ToOwned + ?Sized,
+{
+    const NULLABLE: bool = T::NULLABLE;
+
+    fn serialize(&self) -> Value {
+        (**self).serialize()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        (**self).serialize_to(serializer)
+    }
+}
+
+impl<T> Serialize for Cell<T>
+where
+    T: Serialize + Copy,
+{
+    const NULLABLE: bool = T::NULLABLE;
+
+    fn serialize(&self) -> Value {
+        self.get().serialize()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.get().serialize_to(serializer)
+    }
+}
+
+/// Panics if the value is currently mutably borrowed.
+impl<T> Serialize for RefCell<T>
+where
+    T: Serialize + ?Sized,
+{
+    const NULLABLE: bool = T::NULLABLE;
+
+    fn serialize(&self) -> Value {
+        self.borrow().serialize()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.borrow().serialize_to(serializer)
+    }
+}
+
+impl Serialize for () {
+    const NULLABLE: bool = true;
+
+    fn serialize(&self) -> Value {
+        Value::null()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.value(Scalar::Null)
+    }
+}
+
+impl Serialize for Value {
+    const NULLABLE: bool = true;
+
+    fn serialize(&self) -> Value {
+        self.clone()
+    }
+
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        match self {
+            Value::Token(token) => match token.token_type {
+                TokenType::String(_) => serializer.value(Scalar::Str(&token.literal)),
+                TokenType::ReservedString if token.literal == "null" => {
+                    serializer.value(Scalar::Null)
+                }
+                TokenType::ReservedString if token.literal == "true" => {
+                    serializer.value(Scalar::Bool(true))
+                }
+                TokenType::ReservedString if token.literal == "false" => {
+                    serializer.value(Scalar::Bool(false))
+                }
+                _ => serializer.value(Scalar::Number(&token.literal)),
+            },
+            Value::Object(object) => {
+                serializer.begin_object()?;
+                for (key, val) in object {
+                    serializer.key(key)?;
+                    val.serialize_to(serializer)?;
+                }
+                serializer.end()
+            }
+            Value::Array(array) => {
+                serializer.begin_array()?;
+                for val in array {
+                    val.serialize_to(serializer)?;
+                }
+                serializer.end()
+            }
+        }
+    }
+}
+
+macro_rules! serialize_tuple {
+    ($(($($index:tt $name:ident)+))+) => {$(
+        impl<$($name),+> Serialize for ($($name,)+)
+        where
+            $($name: Serialize,)+
+        {
+            fn serialize(&self) -> Value {
+                Value::Array(Vec::from([$(self.$index.serialize()),+]))
+            }
+
+            fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+                serializer.begin_array()?;
+                $(self.$index.serialize_to(serializer)?;)+
+                serializer.end()
+            }
+        }
+    )+};
+}
+
+serialize_tuple! {
+    (0 T0)
+    (0 T0 1 T1)
+    (0 T0 1 T1 2 T2)
+    (0 T0 1 T1 2 T2 3 T3)
+    (0 T0 1 T1 2 T2 3 T3 4 T4)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14)
+    (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15)
 }
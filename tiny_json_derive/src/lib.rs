@@ -3,8 +3,11 @@ extern crate syn;
 extern crate quote;
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput, Fields, Data};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed, Generics, Index, Data, DataEnum, Ident, Variant, WherePredicate, Type, PathArguments, GenericArgument};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
 use syn::Attribute;
 use syn::Meta;
 use syn::Lit;
@@ -33,50 +36,898 @@ impl RenameAttr {
     }
 }
 
-#[proc_macro_derive(Deserialize, attributes(Rename))]
+// Fields marked #[Coerce] are decoded with every coercion enabled
+fn has_coerce_attr(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident("Coerce"))
+}
+
+// Structs marked #[DenyUnknownFields] reject members they don't declare
+fn has_deny_unknown_fields_attr(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident("DenyUnknownFields"))
+}
+
+// Enums marked #[IntegerDiscriminant] are encoded as their discriminant rather than their variant name
+fn has_integer_discriminant_attr(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident("IntegerDiscriminant"))
+}
+
+// The JSON name of each variant, in declaration order
+fn variant_names(data_enum: &DataEnum) -> Vec<String> {
+    data_enum.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        RenameAttr::from_attributes(&variant.attrs).map_or_else(
+            || format!("{}", quote!(#variant_name)),
+            |RenameAttr(s)| s,
+        )
+    }).collect()
+}
+
+fn require_unit_variants(data_enum: &DataEnum) {
+    if data_enum.variants.iter().any(|variant| !matches!(variant.fields, Fields::Unit)) {
+        panic!("#[IntegerDiscriminant] requires an enum with unit variants only");
+    }
+}
+
+// Whether values of `ty` never encode as objects, judging by the type's name
+fn never_object(ty: &Type) -> bool {
+    match ty {
+        Type::Array(_) | Type::Slice(_) | Type::Tuple(_) => true,
+        Type::Reference(reference) => never_object(&reference.elem),
+        Type::Paren(paren) => never_object(&paren.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            let segment = match path.path.segments.last() {
+                Some(segment) => segment,
+                None => return false,
+            };
+            match segment.ident.to_string().as_str() {
+                "bool" | "char" | "str" | "String" | "Option" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "f32" | "f64" | "Vec" | "VecDeque" | "LinkedList" | "BinaryHeap" | "BTreeSet" | "HashSet" => true,
+                name if name.starts_with("NonZero") => true,
+                // Pointers and cells encode as what they hold
+                "Box" | "Rc" | "Arc" | "Cell" | "RefCell" | "Cow" => match &segment.arguments {
+                    PathArguments::AngleBracketed(arguments) => arguments.args.iter().any(|argument| {
+                        matches!(argument, GenericArgument::Type(inner) if never_object(inner))
+                    }),
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+// Internally tagged newtype variants put the tag among the members of their content
+fn require_object_content(variant: &Variant, ty: &Type) {
+    if never_object(ty) {
+        panic!(
+            "Internally tagged variant {} can't hold `{}`, which does not encode as an object",
+            variant.ident,
+            quote!(#ty).to_string().replace(' ', "")
+        );
+    }
+}
+
+fn string_attr(attrs: &[Attribute], name: &str) -> Option<String> {
+    attrs.iter().find_map(|attr| {
+        if !attr.path.is_ident(name) {
+            return None;
+        }
+        match attr.parse_meta() {
+            Ok(Meta::NameValue(meta)) => match meta.lit {
+                Lit::Str(lit) => Some(lit.value()),
+                _ => None,
+            },
+            _ => None,
+        }
+    })
+}
+
+// Bounds each type parameter by `bound`, unless the derive's own bound attribute or #[Bound = "..."]
+// gives the where-predicates to use instead
+fn with_bounds(generics: &Generics, attrs: &[Attribute], bound_attr: &str, bound: proc_macro2::TokenStream) -> Generics {
+    let mut generics = generics.clone();
+    match string_attr(attrs, bound_attr).or_else(|| string_attr(attrs, "Bound")) {
+        Some(predicates) => {
+            let predicates = Punctuated::<WherePredicate, syn::Token![,]>::parse_terminated
+                .parse_str(&predicates)
+                .unwrap_or_else(|error| panic!("Invalid bound {:?}: {}", predicates, error));
+            generics.make_where_clause().predicates.extend(predicates);
+        }
+        None => {
+            for param in generics.type_params_mut() {
+                param.bounds.push(syn::parse_quote!(#bound));
+            }
+        }
+    }
+    generics
+}
+
+// How an enum records which variant a value holds, after serde's representations
+enum Tagging {
+    // {"Variant": content}
+    External,
+    // {"tag": "Variant", ...content members}
+    Internal(String),
+    // {"tag": "Variant", "content": content}
+    Adjacent(String, String),
+    // content alone, decoded by trying each variant in order
+    Untagged,
+}
+
+impl Tagging {
+    fn from_attributes(attrs: &[Attribute]) -> Self {
+        let untagged = attrs.iter().any(|attr| attr.path.is_ident("Untagged"));
+        match (string_attr(attrs, "Tag"), string_attr(attrs, "Content"), untagged) {
+            (None, None, false) => Tagging::External,
+            (Some(tag), None, false) => Tagging::Internal(tag),
+            (Some(tag), Some(content), false) => Tagging::Adjacent(tag, content),
+            (None, None, true) => Tagging::Untagged,
+            (None, Some(_), false) => panic!("#[Content] requires #[Tag]"),
+            _ => panic!("#[Untagged] can't be combined with #[Tag] or #[Content]"),
+        }
+    }
+}
+
+fn field_key(field: &Field) -> String {
+    let field_name = field.ident.as_ref().unwrap();
+    RenameAttr::from_attributes(&field.attrs).map_or_else(
+        || format!("{}", quote!(#field_name)),
+        |RenameAttr(s)| s,
+    )
+}
+
+fn field_coercion(field: &Field) -> proc_macro2::TokenStream {
+    if has_coerce_attr(&field.attrs) {
+        quote! { coercion.union(serializer::Coercion::LENIENT) }
+    } else {
+        quote! { coercion }
+    }
+}
+
+// Reads the field from `mapper`, lifting the mapper's coercion for fields marked `#[Coerce]`
+fn deserialize_field_from(field: &Field) -> proc_macro2::TokenStream {
+    let ty = &field.ty;
+    if !has_coerce_attr(&field.attrs) {
+        return quote! { <#ty as serializer::Deserialize>::deserialize_from(mapper) };
+    }
+    quote! {
+        {
+            let coercion = mapper.coercion;
+            mapper.coercion = coercion.union(serializer::Coercion::LENIENT);
+            let res = <#ty as serializer::Deserialize>::deserialize_from(mapper);
+            mapper.coercion = coercion;
+            res
+        }
+    }
+}
+
+// The names the fields of a variant are bound to, by the same slot naming as struct fields
+fn variant_bindings(fields: &Fields) -> Vec<Ident> {
+    fields.iter().enumerate().map(|(index, field)| match &field.ident {
+        Some(field_name) => format_ident!("__field_{}", field_name),
+        None => format_ident!("__field_{}", index),
+    }).collect()
+}
+
+// Matches `*self` against the variant, binding its fields by reference
+fn variant_pattern(name: &Ident, variant: &Variant) -> proc_macro2::TokenStream {
+    let variant_name = &variant.ident;
+    let bindings = variant_bindings(&variant.fields);
+    match &variant.fields {
+        Fields::Unit => quote! { #name::#variant_name },
+        Fields::Unnamed(_) => quote! { #name::#variant_name(#(ref #bindings),*) },
+        Fields::Named(fields) => {
+            let field_names = fields.named.iter().map(|field| field.ident.as_ref().unwrap());
+            quote! { #name::#variant_name { #(#field_names: ref #bindings),* } }
+        }
+    }
+}
+
+// An expression decoding the variant from `content`, run in a closure so the caller can
+// place its errors under the member holding the content
+fn deserialize_variant(name: &Ident, variant: &Variant) -> proc_macro2::TokenStream {
+    let variant_name = &variant.ident;
+    let bindings = variant_bindings(&variant.fields);
+    let body = match &variant.fields {
+        Fields::Unit => quote! {
+            <() as serializer::Deserialize>::deserialize_with(content, coercion)?;
+            Ok(#name::#variant_name)
+        },
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let coercion = field_coercion(&fields.unnamed[0]);
+            quote! {
+                Ok(#name::#variant_name(serializer::Deserialize::deserialize_with(content, #coercion)?))
+            }
+        }
+        Fields::Unnamed(fields) => {
+            let types = fields.unnamed.iter().map(|field| &field.ty);
+            quote! {
+                let (#(#bindings,)*) = <(#(#types,)*) as serializer::Deserialize>::deserialize_with(content, coercion)?;
+                Ok(#name::#variant_name(#(#bindings),*))
+            }
+        }
+        Fields::Named(fields) => {
+            let field_names = fields.named.iter().map(|field| field.ident.as_ref().unwrap());
+            let decode_fields = fields.named.iter().zip(&bindings).map(|(field, binding)| {
+                let field_key = field_key(field);
+                let ty = &field.ty;
+                let coercion = field_coercion(field);
+                quote! { let #binding = content.get_value_with::<#ty>(#field_key, #coercion)?; }
+            });
+            quote! {
+                let content = match content {
+                    None => return Err(serializer::DecodeError::missing_value()),
+                    Some(v) if v.json_type() != serializer::JsonType::Object => {
+                        return Err(serializer::DecodeError::invalid_type(
+                            serializer::JsonType::Object,
+                            v.json_type(),
+                        ));
+                    }
+                    Some(v) => v,
+                };
+                #(#decode_fields)*
+                Ok(#name::#variant_name { #(#field_names: #bindings),* })
+            }
+        }
+    };
+    quote! {
+        (|| -> Result<Self, serializer::DecodeError> { #body })()
+    }
+}
+
+// Slots for the fields of an object read from `mapper`, the match arms filling them from
+// its members and the initializers taking each field out of its slot. Missing fields get
+// the same treatment as in `deserialize`
+fn deserialize_fields_from(fields: &Punctuated<Field, Comma>) -> (Vec<proc_macro2::TokenStream>, Vec<proc_macro2::TokenStream>, Vec<proc_macro2::TokenStream>) {
+    let field_slots = fields.iter().map(|field| {
+        let slot = format_ident!("__field_{}", field.ident.as_ref().unwrap());
+        let ty = &field.ty;
+        quote! { let mut #slot: Option<#ty> = None; }
+    }).collect();
+
+    let field_matches = fields.iter().map(|field| {
+        let slot = format_ident!("__field_{}", field.ident.as_ref().unwrap());
+        let field_key = field_key(field);
+        let read = deserialize_field_from(field);
+        quote! {
+            #field_key => #slot = Some(#read.map_err(|e| e.at_field(#field_key))?),
+        }
+    }).collect();
+
+    let slot_initializers = fields.iter().map(|field| {
+        let field_name = field.ident.as_ref().unwrap();
+        let slot = format_ident!("__field_{}", field_name);
+        let field_key = field_key(field);
+        quote! {
+            #field_name: match #slot {
+                Some(val) => val,
+                None => serializer::Deserialize::deserialize(None)
+                    .map_err(|_| serializer::DecodeError::missing_field(#field_key))?,
+            }
+        }
+    }).collect();
+
+    (field_slots, field_matches, slot_initializers)
+}
+
+// An expression collecting the variant from `content` into `errors` as `deserialize_all`
+// does, returning `None` if there were any
+fn collect_variant(name: &Ident, variant: &Variant) -> proc_macro2::TokenStream {
+    let variant_name = &variant.ident;
+    let bindings = variant_bindings(&variant.fields);
+    match &variant.fields {
+        Fields::Unit => quote! {
+            <() as serializer::Deserialize>::deserialize_all(content, coercion, errors).map(|()| #name::#variant_name)
+        },
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let ty = &fields.unnamed[0].ty;
+            let coercion = field_coercion(&fields.unnamed[0]);
+            quote! {
+                <#ty as serializer::Deserialize>::deserialize_all(content, #coercion, errors).map(#name::#variant_name)
+            }
+        }
+        Fields::Unnamed(fields) => {
+            let types = fields.unnamed.iter().map(|field| &field.ty);
+            quote! {
+                <(#(#types,)*) as serializer::Deserialize>::deserialize_all(content, coercion, errors)
+                    .map(|(#(#bindings,)*)| #name::#variant_name(#(#bindings),*))
+            }
+        }
+        Fields::Named(fields) => {
+            let field_names = fields.named.iter().map(|field| field.ident.as_ref().unwrap());
+            let collect_fields = fields.named.iter().zip(&bindings).map(|(field, binding)| {
+                let field_key = field_key(field);
+                let ty = &field.ty;
+                let coercion = field_coercion(field);
+                quote! { let #binding = content.get_value_all::<#ty>(#field_key, #coercion, errors); }
+            });
+            quote! {
+                (|| {
+                    let content = match content {
+                        None => {
+                            errors.push(serializer::DecodeError::missing_value());
+                            return None;
+                        }
+                        Some(v) if v.json_type() != serializer::JsonType::Object => {
+                            errors.push(serializer::DecodeError::invalid_type(
+                                serializer::JsonType::Object,
+                                v.json_type(),
+                            ));
+                            return None;
+                        }
+                        Some(v) => v,
+                    };
+                    #(#collect_fields)*
+                    Some(#name::#variant_name { #(#field_names: #bindings?),* })
+                })()
+            }
+        }
+    }
+}
+
+// An expression reading the content of the variant from `mapper`
+fn deserialize_variant_from(name: &Ident, variant: &Variant) -> proc_macro2::TokenStream {
+    let variant_name = &variant.ident;
+    let bindings = variant_bindings(&variant.fields);
+    match &variant.fields {
+        Fields::Unit => quote! {
+            <() as serializer::Deserialize>::deserialize_from(mapper).map(|()| #name::#variant_name)
+        },
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let read = deserialize_field_from(&fields.unnamed[0]);
+            quote! {
+                {
+                    let res = #read;
+                    res.map(#name::#variant_name)
+                }
+            }
+        }
+        Fields::Unnamed(fields) => {
+            let types = fields.unnamed.iter().map(|field| &field.ty);
+            quote! {
+                <(#(#types,)*) as serializer::Deserialize>::deserialize_from(mapper)
+                    .map(|(#(#bindings,)*)| #name::#variant_name(#(#bindings),*))
+            }
+        }
+        Fields::Named(fields) => {
+            let (field_slots, field_matches, slot_initializers) = deserialize_fields_from(&fields.named);
+            quote! {
+                (|| -> Result<Self, serializer::DecodeError> {
+                    #(#field_slots)*
+                    mapper.expect_type(serializer::JsonType::Object)?;
+                    mapper.begin_object()?;
+                    while let Some(key) = mapper.next_key()? {
+                        match key.as_str() {
+                            #(#field_matches)*
+                            _ => mapper.skip_element()?,
+                        }
+                    }
+                    Ok(#name::#variant_name { #(#slot_initializers),* })
+                })()
+            }
+        }
+    }
+}
+
+fn deserialize_enum(name: &Ident, generics: &Generics, data_enum: &DataEnum, attrs: &[Attribute]) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let variants = data_enum.variants.iter().map(|variant| &variant.ident).collect::<Vec<_>>();
+    let variant_keys = variant_names(data_enum);
+    let is_unit = data_enum.variants.iter().map(|variant| matches!(variant.fields, Fields::Unit)).collect::<Vec<_>>();
+
+    // Decoders beyond `deserialize_with`, for the tagging that can use them
+    let mut extra = quote! {};
+    let parse = if has_integer_discriminant_attr(attrs) {
+        require_unit_variants(data_enum);
+        let from_discriminant = quote! {
+            #(
+                if discriminant == #name::#variants as i64 {
+                    return Ok(#name::#variants);
+                }
+            )*
+            let literal = serializer::Serialize::serialize(&discriminant).encode_json();
+            Err(serializer::DecodeError::invalid_value::<Self>(&literal))
+        };
+        extra = quote! {
+            fn deserialize_from(mapper: &mut serializer::Mapper) -> Result<Self, serializer::DecodeError> {
+                let discriminant = <i64 as serializer::Deserialize>::deserialize_from(mapper)?;
+                #from_discriminant
+            }
+        };
+        quote! {
+            let discriminant: i64 = serializer::Deserialize::deserialize_with(value, coercion)?;
+            #from_discriminant
+        }
+    } else {
+        let unknown_variant = quote! {
+            other => Err(serializer::DecodeError::invalid_value::<Self>(other)),
+        };
+        // Whatever the content collectors don't handle fails as a whole
+        let single_error = quote! {
+            _ => Self::deserialize_with(value, coercion)
+                .map_err(|error| errors.push(error))
+                .ok(),
+        };
+        let arms = data_enum.variants.iter().zip(&variant_keys).zip(&is_unit);
+        match Tagging::from_attributes(attrs) {
+            Tagging::External => {
+                let unit_arms = arms.clone().filter(|(_, unit)| **unit).map(|((variant, key), _)| {
+                    let variant_name = &variant.ident;
+                    quote! { #key => Ok(#name::#variant_name), }
+                });
+                // Unit variants also decode from `{"Variant": null}`
+                let object_arms = arms.clone().map(|((variant, key), _)| {
+                    let decode = deserialize_variant(name, variant);
+                    quote! {
+                        #key => {
+                            let content = Some(content);
+                            #decode.map_err(|e| e.at_field(#key))
+                        }
+                    }
+                });
+                let collect_arms = arms.clone().map(|((variant, key), _)| {
+                    let collect = collect_variant(name, variant);
+                    quote! {
+                        Some((#key, content)) => {
+                            let mut found = serializer::Vec::new();
+                            let res = {
+                                let errors = &mut found;
+                                #collect
+                            };
+                            errors.extend(found.into_iter().map(|error| error.at_field(#key)));
+                            res
+                        }
+                    }
+                });
+                let stream_arms = arms.map(|((variant, key), _)| {
+                    let read = deserialize_variant_from(name, variant);
+                    quote! {
+                        #key => {
+                            let res = #read;
+                            res.map_err(|e| e.at_field(#key))
+                        }
+                    }
+                });
+                extra = quote! {
+                    fn deserialize_all(value: Option<&serializer::Value>, coercion: serializer::Coercion, errors: &mut serializer::Vec<serializer::DecodeError>) -> Option<Self> {
+                        let member = match value {
+                            Some(serializer::Value::Object(object)) if object.len() == 1 => object.iter().next(),
+                            _ => None,
+                        };
+                        match member.map(|(variant, content)| (variant.as_str(), Some(content))) {
+                            #(#collect_arms)*
+                            #single_error
+                        }
+                    }
+
+                    fn deserialize_from(mapper: &mut serializer::Mapper) -> Result<Self, serializer::DecodeError> {
+                        if mapper.next_type() != Some(serializer::JsonType::Object) {
+                            let value = mapper.parse_element()?;
+                            return Self::deserialize_with(Some(&value), mapper.coercion);
+                        }
+                        mapper.begin_object()?;
+                        let res = match mapper.next_key()? {
+                            Some(variant) => match variant.as_str() {
+                                #(#stream_arms)*
+                                #unknown_variant
+                            }?,
+                            None => return Err(serializer::DecodeError::invalid_length(1, 0)),
+                        };
+                        let mut len = 1;
+                        while mapper.next_key()?.is_some() {
+                            mapper.skip_element()?;
+                            len += 1;
+                        }
+                        if len != 1 {
+                            return Err(serializer::DecodeError::invalid_length(1, len));
+                        }
+                        Ok(res)
+                    }
+                };
+                quote! {
+                    let _ = coercion;
+                    match value {
+                        Some(serializer::Value::Object(object)) => {
+                            let (variant, content) = match object.iter().next() {
+                                Some(member) if object.len() == 1 => member,
+                                _ => return Err(serializer::DecodeError::invalid_length(1, object.len())),
+                            };
+                            let _ = content;
+                            match variant.as_str() {
+                                #(#object_arms)*
+                                #unknown_variant
+                            }
+                        }
+                        _ => match serializer::deserializers::parse_str(value)? {
+                            #(#unit_arms)*
+                            #unknown_variant
+                        },
+                    }
+                }
+            }
+            Tagging::Internal(tag) => {
+                let arms = arms.map(|((variant, key), _)| {
+                    let variant_name = &variant.ident;
+                    match &variant.fields {
+                        Fields::Unit => (
+                            quote! { #key => Ok(#name::#variant_name), },
+                            quote! { Ok(#key) => Some(#name::#variant_name), },
+                        ),
+                        Fields::Named(_) => {
+                            let decode = deserialize_variant(name, variant);
+                            let collect = collect_variant(name, variant);
+                            (
+                                quote! {
+                                    #key => {
+                                        let content = value;
+                                        #decode
+                                    }
+                                },
+                                quote! {
+                                    Ok(#key) => {
+                                        let content = value;
+                                        #collect
+                                    }
+                                },
+                            )
+                        }
+                        // The content reads the members of the tagged object around the tag
+                        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                            let field = &fields.unnamed[0];
+                            require_object_content(variant, &field.ty);
+                            let ty = &field.ty;
+                            let coercion = field_coercion(field);
+                            let collect = quote! {
+                                value.and_then(|value| <#ty as serializer::Deserialize>::deserialize_members(value, #tag, #coercion, errors))
+                                    .map(#name::#variant_name)
+                            };
+                            (
+                                quote! {
+                                    #key => {
+                                        let mut found = serializer::Vec::new();
+                                        let res = {
+                                            let errors = &mut found;
+                                            #collect
+                                        };
+                                        res.ok_or_else(|| found.remove(0))
+                                    }
+                                },
+                                quote! { Ok(#key) => #collect, },
+                            )
+                        }
+                        Fields::Unnamed(_) => panic!("Internally tagged enums can't have tuple variants"),
+                    }
+                });
+                let (arms, collect_arms): (Vec<_>, Vec<_>) = arms.unzip();
+                extra = quote! {
+                    fn deserialize_all(value: Option<&serializer::Value>, coercion: serializer::Coercion, errors: &mut serializer::Vec<serializer::DecodeError>) -> Option<Self> {
+                        match serializer::deserializers::parse_tag(value, #tag) {
+                            #(#collect_arms)*
+                            #single_error
+                        }
+                    }
+                };
+                quote! {
+                    let _ = coercion;
+                    match serializer::deserializers::parse_tag(value, #tag)? {
+                        #(#arms)*
+                        other => Err(serializer::DecodeError::invalid_value::<Self>(other).at_field(#tag)),
+                    }
+                }
+            }
+            Tagging::Adjacent(tag, content_key) => {
+                let find_content = quote! {
+                    let content = match value {
+                        Some(serializer::Value::Object(object)) => object.get(#content_key),
+                        _ => None,
+                    };
+                };
+                let arms = arms.map(|((variant, key), unit)| {
+                    let variant_name = &variant.ident;
+                    if *unit {
+                        return (
+                            quote! { #key => Ok(#name::#variant_name), },
+                            quote! { Ok(#key) => Some(#name::#variant_name), },
+                        );
+                    }
+                    let decode = deserialize_variant(name, variant);
+                    let collect = collect_variant(name, variant);
+                    (
+                        quote! {
+                            #key => {
+                                #find_content
+                                #decode.map_err(|e| match content {
+                                    None => serializer::DecodeError::missing_field(#content_key),
+                                    Some(_) => e.at_field(#content_key),
+                                })
+                            }
+                        },
+                        quote! {
+                            Ok(#key) => {
+                                #find_content
+                                let mut found = serializer::Vec::new();
+                                let res = {
+                                    let errors = &mut found;
+                                    #collect
+                                };
+                                match content {
+                                    None if !found.is_empty() => errors.push(serializer::DecodeError::missing_field(#content_key)),
+                                    _ => errors.extend(found.into_iter().map(|error| error.at_field(#content_key))),
+                                }
+                                res
+                            }
+                        },
+                    )
+                });
+                let (arms, collect_arms): (Vec<_>, Vec<_>) = arms.unzip();
+                extra = quote! {
+                    fn deserialize_all(value: Option<&serializer::Value>, coercion: serializer::Coercion, errors: &mut serializer::Vec<serializer::DecodeError>) -> Option<Self> {
+                        match serializer::deserializers::parse_tag(value, #tag) {
+                            #(#collect_arms)*
+                            #single_error
+                        }
+                    }
+                };
+                quote! {
+                    let _ = coercion;
+                    match serializer::deserializers::parse_tag(value, #tag)? {
+                        #(#arms)*
+                        other => Err(serializer::DecodeError::invalid_value::<Self>(other).at_field(#tag)),
+                    }
+                }
+            }
+            Tagging::Untagged => {
+                let attempts = data_enum.variants.iter().map(|variant| deserialize_variant(name, variant));
+                quote! {
+                    let _ = coercion;
+                    let content = value;
+                    #(
+                        if let Ok(res) = #attempts {
+                            return Ok(res);
+                        }
+                    )*
+                    Err(match content {
+                        Some(v) => serializer::DecodeError::invalid_value::<Self>(&v.encode_json()),
+                        None => serializer::DecodeError::missing_value(),
+                    })
+                }
+            }
+        }
+    };
+
+    // Only externally tagged and integer enums stream; the others may meet their tag
+    // after the content and keep the default, which reads the element whole
+    let expanded = quote! {
+        impl #impl_generics serializer::Deserialize for #name #ty_generics #where_clause {
+            fn deserialize(value: Option<&serializer::Value>) -> Result<Self, serializer::DecodeError> {
+                Self::deserialize_with(value, serializer::Coercion::STRICT)
+            }
+
+            fn deserialize_with(value: Option<&serializer::Value>, coercion: serializer::Coercion) -> Result<Self, serializer::DecodeError> {
+                #parse
+            }
+
+            #extra
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+// Newtypes decode as the value they wrap, other tuple structs as arrays
+fn deserialize_tuple_struct(name: &Ident, generics: &Generics, fields: &FieldsUnnamed) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let expanded = if fields.unnamed.len() == 1 {
+        let field = &fields.unnamed[0];
+        let ty = &field.ty;
+        let coercion = field_coercion(field);
+        let read = deserialize_field_from(field);
+        quote! {
+            impl #impl_generics serializer::Deserialize for #name #ty_generics #where_clause {
+                fn deserialize(value: Option<&serializer::Value>) -> Result<Self, serializer::DecodeError> {
+                    Self::deserialize_with(value, serializer::Coercion::STRICT)
+                }
+
+                fn deserialize_with(value: Option<&serializer::Value>, coercion: serializer::Coercion) -> Result<Self, serializer::DecodeError> {
+                    <#ty as serializer::Deserialize>::deserialize_with(value, #coercion).map(#name)
+                }
+
+                fn deserialize_from(mapper: &mut serializer::Mapper) -> Result<Self, serializer::DecodeError> {
+                    let res = #read;
+                    res.map(#name)
+                }
+
+                fn deserialize_null() -> Option<Self> {
+                    <#ty as serializer::Deserialize>::deserialize_null().map(#name)
+                }
+
+                fn deserialize_all(value: Option<&serializer::Value>, coercion: serializer::Coercion, errors: &mut serializer::Vec<serializer::DecodeError>) -> Option<Self> {
+                    <#ty as serializer::Deserialize>::deserialize_all(value, #coercion, errors).map(#name)
+                }
+            }
+        }
+    } else {
+        let types = fields.unnamed.iter().map(|field| &field.ty).collect::<Vec<_>>();
+        let bindings = (0..types.len()).map(|index| format_ident!("__field_{}", index)).collect::<Vec<_>>();
+        quote! {
+            impl #impl_generics serializer::Deserialize for #name #ty_generics #where_clause {
+                fn deserialize(value: Option<&serializer::Value>) -> Result<Self, serializer::DecodeError> {
+                    Self::deserialize_with(value, serializer::Coercion::STRICT)
+                }
+
+                fn deserialize_with(value: Option<&serializer::Value>, coercion: serializer::Coercion) -> Result<Self, serializer::DecodeError> {
+                    let (#(#bindings,)*) = <(#(#types,)*) as serializer::Deserialize>::deserialize_with(value, coercion)?;
+                    Ok(#name(#(#bindings),*))
+                }
+
+                fn deserialize_from(mapper: &mut serializer::Mapper) -> Result<Self, serializer::DecodeError> {
+                    let (#(#bindings,)*) = <(#(#types,)*) as serializer::Deserialize>::deserialize_from(mapper)?;
+                    Ok(#name(#(#bindings),*))
+                }
+
+                fn deserialize_all(value: Option<&serializer::Value>, coercion: serializer::Coercion, errors: &mut serializer::Vec<serializer::DecodeError>) -> Option<Self> {
+                    let (#(#bindings,)*) = <(#(#types,)*) as serializer::Deserialize>::deserialize_all(value, coercion, errors)?;
+                    Some(#name(#(#bindings),*))
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+// Unit structs decode from null, or from a missing member
+fn deserialize_unit_struct(name: &Ident, generics: &Generics) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let expanded = quote! {
+        impl #impl_generics serializer::Deserialize for #name #ty_generics #where_clause {
+            fn deserialize(value: Option<&serializer::Value>) -> Result<Self, serializer::DecodeError> {
+                <() as serializer::Deserialize>::deserialize(value).map(|()| #name)
+            }
+
+            fn deserialize_null() -> Option<Self> {
+                Some(#name)
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro_derive(Deserialize, attributes(Rename, Coerce, DenyUnknownFields, IntegerDiscriminant, Tag, Content, Untagged, Bound, DeserializeBound))]
 pub fn deserialize_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let name = &input.ident;
+    let generics = with_bounds(&input.generics, &input.attrs, "DeserializeBound", quote! { serializer::Deserialize });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    if let Data::Enum(data_enum) = &input.data {
+        return deserialize_enum(name, &generics, data_enum, &input.attrs);
+    }
 
     let fields = match &input.data {
         Data::Struct(data_struct) => match &data_struct.fields {
             Fields::Named(fields_named) => &fields_named.named,
-            Fields::Unnamed(_) => panic!("Unnamed fields are not supported"),
-            Fields::Unit => panic!("Unit structs are not supported"),
+            Fields::Unnamed(fields_unnamed) => return deserialize_tuple_struct(name, &generics, fields_unnamed),
+            Fields::Unit => return deserialize_unit_struct(name, &generics),
         },
-        _ => panic!("Deserialize can only be implemented for structs"),
+        _ => panic!("Deserialize can only be implemented for structs and enums"),
     };
 
     let deserialize_fields = fields.iter().map(|field| {
         let field_name = field.ident.as_ref().unwrap();
-        let rename_attr = RenameAttr::from_attributes(&field.attrs);
-        let field_key = rename_attr.map_or_else(
-            || format!("{}", quote!(#field_name)),
-            |RenameAttr(s)| s,
-        );
+        let field_key = field_key(field);
         let ty = &field.ty;
+        let coercion = field_coercion(field);
+
+        // Bound under the slot name so fields can't shadow `value` or `coercion`
+        let slot = format_ident!("__field_{}", field_name);
 
         quote! {
-        let #field_name = match value.get_value::<#ty>(&#field_key) {
+        let #slot = match value.get_value_with::<#ty>(&#field_key, #coercion) {
             Ok(val) => val,
             Err(e) => return Err(e),
         };
     }
     }).collect::<Vec<_>>();
 
+    let field_keys = fields.iter().map(field_key).collect::<Vec<_>>();
+
+    let collect_fields = fields.iter().zip(&field_keys).map(|(field, field_key)| {
+        let ty = &field.ty;
+        let coercion = field_coercion(field);
+        let slot = format_ident!("__field_{}", field.ident.as_ref().unwrap());
+        quote! { let #slot = value.get_value_all::<#ty>(&#field_key, #coercion, errors); }
+    }).collect::<Vec<_>>();
+
+    let collected_initializers = fields.iter().map(|field| {
+        let field_name = field.ident.as_ref().unwrap();
+        let slot = format_ident!("__field_{}", field_name);
+        quote! { #field_name: #slot? }
+    }).collect::<Vec<_>>();
+
+    let deny_unknown_fields = has_deny_unknown_fields_attr(&input.attrs);
+
+    let check_unknown_fields = if deny_unknown_fields {
+        quote! {
+            const FIELDS: &[&str] = &[#(#field_keys),*];
+            if let serializer::Value::Object(object) = value {
+                if let Some(key) = object.keys().find(|key| !FIELDS.contains(&key.as_str())) {
+                    return Err(serializer::DecodeError::unknown_field(key));
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // `skipped` passes over the tag when an internally tagged enum holds the struct
+    let collect_members = |skipped: proc_macro2::TokenStream| {
+        let collect_unknown_fields = if deny_unknown_fields {
+            quote! {
+                const FIELDS: &[&str] = &[#(#field_keys),*];
+                if let serializer::Value::Object(object) = value {
+                    for key in object.keys().filter(|key| !FIELDS.contains(&key.as_str()) #skipped) {
+                        errors.push(serializer::DecodeError::unknown_field(key));
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+        quote! {
+            let reported = errors.len();
+            #collect_unknown_fields
+            let _ = coercion;
+
+            #(#collect_fields)*
+
+            if errors.len() != reported {
+                return None;
+            }
+            Some(#name {
+                #(#collected_initializers),*
+            })
+        }
+    };
+    let collect_fields = collect_members(quote! {});
+    let collect_tagged_fields = collect_members(quote! { && key.as_str() != tag });
+
+    let unknown_field_arm = if deny_unknown_fields {
+        quote! { _ => return Err(serializer::DecodeError::unknown_field(&key)), }
+    } else {
+        quote! { _ => mapper.skip_element()?, }
+    };
+
     let field_initializers = fields.iter().map(|field| {
         let field_name = field.ident.as_ref().unwrap();
-        quote! { #field_name }
+        let slot = format_ident!("__field_{}", field_name);
+        quote! { #field_name: #slot }
     }).collect::<Vec<_>>();
 
+    let (field_slots, field_matches, slot_initializers) = deserialize_fields_from(fields);
+
     let expanded = quote! {
-        impl serializer::Deserialize for #name {
+        impl #impl_generics serializer::Deserialize for #name #ty_generics #where_clause {
             fn deserialize(value: Option<&serializer::Value>) -> Result<Self, serializer::DecodeError> {
+                Self::deserialize_with(value, serializer::Coercion::STRICT)
+            }
+
+            fn deserialize_with(value: Option<&serializer::Value>, coercion: serializer::Coercion) -> Result<Self, serializer::DecodeError> {
                 let value = match value {
-                    None => {return Err(serializer::DecodeError::ParseError("Could not parse None".to_string()))}
+                    None => {return Err(serializer::DecodeError::missing_value())}
                     Some(v) => {v}
                 };
+                if value.json_type() != serializer::JsonType::Object {
+                    return Err(serializer::DecodeError::invalid_type(
+                        serializer::JsonType::Object,
+                        value.json_type(),
+                    ));
+                }
+                #check_unknown_fields
+                // Structs without fields never read the coercion
+                let _ = coercion;
 
                 #(#deserialize_fields)*
 
@@ -84,46 +935,457 @@ pub fn deserialize_derive(input: TokenStream) -> TokenStream {
                     #(#field_initializers),*
                 })
             }
+
+            fn deserialize_all(value: Option<&serializer::Value>, coercion: serializer::Coercion, errors: &mut serializer::Vec<serializer::DecodeError>) -> Option<Self> {
+                let value = match value {
+                    None => {
+                        errors.push(serializer::DecodeError::missing_value());
+                        return None;
+                    }
+                    Some(v) => {v}
+                };
+                if value.json_type() != serializer::JsonType::Object {
+                    errors.push(serializer::DecodeError::invalid_type(
+                        serializer::JsonType::Object,
+                        value.json_type(),
+                    ));
+                    return None;
+                }
+                #collect_fields
+            }
+
+            fn deserialize_members(value: &serializer::Value, tag: &str, coercion: serializer::Coercion, errors: &mut serializer::Vec<serializer::DecodeError>) -> Option<Self> {
+                let _ = tag;
+                #collect_tagged_fields
+            }
+
+            fn deserialize_from(mapper: &mut serializer::Mapper) -> Result<Self, serializer::DecodeError> {
+                #(#field_slots)*
+
+                mapper.expect_type(serializer::JsonType::Object)?;
+                mapper.begin_object()?;
+                while let Some(key) = mapper.next_key()? {
+                    match key.as_str() {
+                        #(#field_matches)*
+                        #unknown_field_arm
+                    }
+                }
+
+                Ok(#name {
+                    #(#slot_initializers),*
+                })
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+// The content of a data-carrying variant, as a Value expression and as statements streaming it
+fn serialize_variant_content(variant: &Variant) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let bindings = variant_bindings(&variant.fields);
+    match &variant.fields {
+        Fields::Named(fields) => {
+            let (inserts, streams) = serialize_variant_members(fields, &bindings, None);
+            (
+                quote! {
+                    {
+                        let mut object = mapper::Object::new();
+                        #inserts
+                        mapper::Value::Object(object)
+                    }
+                },
+                quote! {
+                    serializer.begin_object()?;
+                    #streams
+                    serializer.end()?;
+                },
+            )
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let binding = &bindings[0];
+            (
+                quote! { serializer::Serialize::serialize(#binding) },
+                quote! { serializer::Serialize::serialize_to(#binding, serializer)?; },
+            )
+        }
+        _ => (
+            quote! { serializer::Serialize::serialize(&(#(#bindings,)*)) },
+            quote! { serializer::Serialize::serialize_to(&(#(#bindings,)*), serializer)?; },
+        ),
+    }
+}
+
+// The members of a struct variant, plus the tag naming the variant when there is one, inserted into
+// `object` and streamed into `serializer`. The stream is in key order, like an encoded `Value`.
+fn serialize_variant_members(fields: &FieldsNamed, bindings: &[Ident], tag: Option<(&str, &str)>) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let field_keys = fields.named.iter().map(field_key).collect::<Vec<_>>();
+    let tag_insert = tag.map(|(tag, key)| quote! {
+        object.insert(#tag.to_owned(), serializer::Serialize::serialize(#key));
+    });
+    let inserts = quote! {
+        #tag_insert
+        #(
+            if !serializer::Serialize::is_absent(#bindings) {
+                object.insert(#field_keys.to_owned(), serializer::Serialize::serialize(#bindings));
+            }
+        )*
+    };
+    let mut streams = field_keys.iter().zip(bindings).map(|(field_key, binding)| {
+        let stream = quote! {
+            if !serializer::Serialize::is_absent(#binding) {
+                serializer.key(#field_key)?;
+                serializer::Serialize::serialize_to(#binding, serializer)?;
+            }
+        };
+        (field_key.clone(), stream)
+    }).collect::<Vec<_>>();
+    if let Some((tag, key)) = tag {
+        let stream = quote! {
+            serializer.key(#tag)?;
+            serializer::Serialize::serialize_to(#key, serializer)?;
+        };
+        streams.push((tag.to_owned(), stream));
+    }
+    streams.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let streams = streams.into_iter().map(|(_, stream)| stream);
+    (inserts, quote! { #(#streams)* })
+}
+
+fn serialize_enum(name: &Ident, generics: &Generics, data_enum: &DataEnum, attrs: &[Attribute]) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let variants = data_enum.variants.iter().map(|variant| &variant.ident).collect::<Vec<_>>();
+    let variant_keys = variant_names(data_enum);
+
+    if has_integer_discriminant_attr(attrs) {
+        require_unit_variants(data_enum);
+        let expanded = quote! {
+            impl #impl_generics serializer::Serialize for #name #ty_generics #where_clause {
+                fn serialize(&self) -> serializer::Value {
+                    match *self {
+                        #(#name::#variants => serializer::Serialize::serialize(&(#name::#variants as i64)),)*
+                    }
+                }
+
+                fn serialize_to<S: serializer::Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+                    match *self {
+                        #(#name::#variants => serializer::Serialize::serialize_to(&(#name::#variants as i64), serializer),)*
+                    }
+                }
+            }
+        };
+        return TokenStream::from(expanded);
+    }
+
+    let tagging = Tagging::from_attributes(attrs);
+
+    // The tag goes among the members of newtype content, which must therefore be an object
+    let mut generics = generics.clone();
+    if let Tagging::Internal(_) = tagging {
+        for variant in &data_enum.variants {
+            match &variant.fields {
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let ty = &fields.unnamed[0].ty;
+                    generics.make_where_clause().predicates.push(syn::parse_quote!(#ty: serializer::SerializeObject));
+                }
+                _ => {}
+            }
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let object_impl = match tagging {
+        Tagging::Internal(_) | Tagging::Adjacent(..) => Some(quote! {
+            impl #impl_generics serializer::SerializeObject for #name #ty_generics #where_clause {}
+        }),
+        _ => None,
+    };
+
+    let patterns = data_enum.variants.iter().map(|variant| variant_pattern(name, variant)).collect::<Vec<_>>();
+    let (values, streams): (Vec<_>, Vec<_>) = data_enum.variants.iter().zip(&variant_keys).map(|(variant, key)| {
+        let unit = matches!(variant.fields, Fields::Unit);
+        match &tagging {
+            Tagging::External if unit => (
+                quote! { serializer::Serialize::serialize(#key) },
+                quote! { serializer::Serialize::serialize_to(#key, serializer) },
+            ),
+            Tagging::External => {
+                let (content_value, content_stream) = serialize_variant_content(variant);
+                (
+                    quote! {
+                        {
+                            let mut object = mapper::Object::new();
+                            object.insert(#key.to_owned(), #content_value);
+                            mapper::Value::Object(object)
+                        }
+                    },
+                    quote! {
+                        serializer.begin_object()?;
+                        serializer.key(#key)?;
+                        #content_stream
+                        serializer.end()
+                    },
+                )
+            }
+            Tagging::Internal(tag) => match &variant.fields {
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    require_object_content(variant, &fields.unnamed[0].ty);
+                    let binding = &variant_bindings(&variant.fields)[0];
+                    (
+                        quote! { serializer::serializers::insert_tag(#binding, #tag, #key) },
+                        quote! { serializer::serializers::serialize_tagged(#binding, #tag, #key, serializer) },
+                    )
+                }
+                Fields::Unnamed(_) => panic!("Internally tagged enums can't have tuple variants"),
+                fields => {
+                    let fields = match fields {
+                        Fields::Named(fields) => fields.clone(),
+                        _ => syn::parse_quote!({}),
+                    };
+                    let bindings = variant_bindings(&variant.fields);
+                    let (inserts, streams) = serialize_variant_members(&fields, &bindings, Some((tag, key)));
+                    (
+                        quote! {
+                            {
+                                let mut object = mapper::Object::new();
+                                #inserts
+                                mapper::Value::Object(object)
+                            }
+                        },
+                        quote! {
+                            serializer.begin_object()?;
+                            #streams
+                            serializer.end()
+                        },
+                    )
+                }
+            },
+            Tagging::Adjacent(tag, content_key) => {
+                let tag_stream = quote! {
+                    serializer.key(#tag)?;
+                    serializer::Serialize::serialize_to(#key, serializer)?;
+                };
+                let (content_value, content_stream) = if unit {
+                    (quote! {}, tag_stream)
+                } else {
+                    let (content_value, content_stream) = serialize_variant_content(variant);
+                    let content_stream = quote! {
+                        serializer.key(#content_key)?;
+                        #content_stream
+                    };
+                    // Streamed in key order, like an encoded `Value`
+                    let streams = if tag < content_key {
+                        quote! { #tag_stream #content_stream }
+                    } else {
+                        quote! { #content_stream #tag_stream }
+                    };
+                    (quote! { object.insert(#content_key.to_owned(), #content_value); }, streams)
+                };
+                (
+                    quote! {
+                        {
+                            let mut object = mapper::Object::new();
+                            object.insert(#tag.to_owned(), serializer::Serialize::serialize(#key));
+                            #content_value
+                            mapper::Value::Object(object)
+                        }
+                    },
+                    quote! {
+                        serializer.begin_object()?;
+                        #content_stream
+                        serializer.end()
+                    },
+                )
+            }
+            Tagging::Untagged if unit => (
+                quote! { mapper::Value::null() },
+                quote! { serializer.value(serializer::Scalar::Null) },
+            ),
+            Tagging::Untagged => {
+                let (content_value, content_stream) = serialize_variant_content(variant);
+                (
+                    content_value,
+                    quote! {
+                        #content_stream
+                        Ok(())
+                    },
+                )
+            }
+        }
+    }).unzip();
+
+    let expanded = quote! {
+        impl #impl_generics serializer::Serialize for #name #ty_generics #where_clause {
+            fn serialize(&self) -> serializer::Value {
+                match *self {
+                    #(#patterns => #values,)*
+                }
+            }
+
+            fn serialize_to<S: serializer::Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+                match *self {
+                    #(#patterns => { #streams })*
+                }
+            }
         }
+
+        #object_impl
     };
 
     TokenStream::from(expanded)
 }
 
-#[proc_macro_derive(Serialize, attributes(Rename))]
+// Newtypes encode as the value they wrap, other tuple structs as arrays
+fn serialize_tuple_struct(name: &Ident, generics: &Generics, fields: &FieldsUnnamed) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let expanded = if fields.unnamed.len() == 1 {
+        let ty = &fields.unnamed[0].ty;
+        quote! {
+            impl #impl_generics serializer::Serialize for #name #ty_generics #where_clause {
+                const NULLABLE: bool = <#ty as serializer::Serialize>::NULLABLE;
+
+                fn serialize(&self) -> serializer::Value {
+                    serializer::Serialize::serialize(&self.0)
+                }
+
+                fn is_absent(&self) -> bool {
+                    serializer::Serialize::is_absent(&self.0)
+                }
+
+                fn serialize_to<S: serializer::Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+                    serializer::Serialize::serialize_to(&self.0, serializer)
+                }
+            }
+        }
+    } else {
+        let indices = (0..fields.unnamed.len()).map(Index::from).collect::<Vec<_>>();
+        quote! {
+            impl #impl_generics serializer::Serialize for #name #ty_generics #where_clause {
+                fn serialize(&self) -> serializer::Value {
+                    serializer::Serialize::serialize(&(#(&self.#indices,)*))
+                }
+
+                fn serialize_to<S: serializer::Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+                    serializer::Serialize::serialize_to(&(#(&self.#indices,)*), serializer)
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+fn serialize_unit_struct(name: &Ident, generics: &Generics) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let expanded = quote! {
+        impl #impl_generics serializer::Serialize for #name #ty_generics #where_clause {
+            const NULLABLE: bool = true;
+
+            fn serialize(&self) -> serializer::Value {
+                mapper::Value::null()
+            }
+
+            fn serialize_to<S: serializer::Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+                serializer.value(serializer::Scalar::Null)
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro_derive(Serialize, attributes(Rename, IntegerDiscriminant, Tag, Content, Untagged, Bound, SerializeBound))]
 pub fn serialize_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let name = &input.ident;
+    let generics = with_bounds(&input.generics, &input.attrs, "SerializeBound", quote! { serializer::Serialize });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    if let Data::Enum(data_enum) = &input.data {
+        return serialize_enum(name, &generics, data_enum, &input.attrs);
+    }
 
     let fields = match input.data {
         Data::Struct(data_struct) => match data_struct.fields {
             Fields::Named(fields_named) => fields_named.named,
-            _ => panic!("Serialize currently only supports structs with named fields"),
+            Fields::Unnamed(fields_unnamed) => return serialize_tuple_struct(name, &generics, &fields_unnamed),
+            Fields::Unit => return serialize_unit_struct(name, &generics),
         },
-        _ => panic!("Serialize can only be implemented for structs"),
+        _ => panic!("Serialize can only be implemented for structs and enums"),
     };
 
     let field_transformations = fields.iter().map(|field| {
         let field_name = &field.ident;
-        let rename_attr = RenameAttr::from_attributes(&field.attrs);
-        let field_key = rename_attr.map_or_else(
-            || format!("{}", quote!(#field_name)),
-            |RenameAttr(s)| s,
-        );
+        let field_key = field_key(field);
 
         quote! {
-            object.insert(#field_key.to_owned(), self.#field_name.serialize());
+            if !serializer::Serialize::is_absent(&self.#field_name) {
+                object.insert(#field_key.to_owned(), serializer::Serialize::serialize(&self.#field_name));
+            }
+        }
+    });
+
+    // Streamed in key order, the order an encoded `Value` has
+    let mut sorted_fields = fields.iter().collect::<Vec<_>>();
+    sorted_fields.sort_by_key(|field| field_key(field));
+    let field_streams = sorted_fields.iter().map(|field| {
+        let field_name = &field.ident;
+        let field_key = field_key(field);
+
+        quote! {
+            if !serializer::Serialize::is_absent(&self.#field_name) {
+                serializer.key(#field_key)?;
+                serializer::Serialize::serialize_to(&self.#field_name, serializer)?;
+            }
         }
     });
 
     let expanded = quote! {
-        impl serializer::Serialize for #name {
+        impl #impl_generics serializer::Serialize for #name #ty_generics #where_clause {
             fn serialize(&self) -> serializer::Value {
                 let mut object = mapper::Object::new();
                 #(#field_transformations)*
                 mapper::Value::Object(object)
             }
+
+            fn serialize_to<S: serializer::Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+                serializer.begin_object()?;
+                #(#field_streams)*
+                serializer.end()
+            }
+        }
+
+        impl #impl_generics serializer::SerializeObject for #name #ty_generics #where_clause {}
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro_derive(JsonKey, attributes(Bound))]
+pub fn json_key_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = &input.ident;
+    let generics = with_bounds(&input.generics, &input.attrs, "Bound", quote! { serializer::JsonKey });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let ty = match &input.data {
+        Data::Struct(data_struct) => match &data_struct.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+            _ => panic!("JsonKey can only be derived for single-field tuple structs"),
+        },
+        _ => panic!("JsonKey can only be derived for single-field tuple structs"),
+    };
+
+    let expanded = quote! {
+        impl #impl_generics serializer::JsonKey for #name #ty_generics #where_clause {
+            fn to_key(&self) -> serializer::Cow<'_, str> {
+                serializer::JsonKey::to_key(&self.0)
+            }
+
+            fn from_key(key: &str) -> Result<Self, serializer::DecodeError> {
+                <#ty as serializer::JsonKey>::from_key(key).map(#name)
+            }
         }
     };
 